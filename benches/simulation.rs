@@ -0,0 +1,130 @@
+//! `cargo bench` — simulation step cost, independent of rendering.
+//!
+//! Baseline (release, single thread):
+//!
+//! | benchmark                                   | time     |
+//! | ------------------------------------------- | -------- |
+//! | lenia_frame/32x32/full/r5                   | 443 µs   |
+//! | lenia_frame/32x32/skip_empty/r5             | 420 µs   |
+//! | lenia_frame/32x32/full/r13                  | 3.11 ms  |
+//! | lenia_frame/32x32/skip_empty/r13            | 3.07 ms  |
+//! | lenia_frame/64x64/full/r5                   | 1.63 ms  |
+//! | lenia_frame/64x64/skip_empty/r5             | 1.61 ms  |
+//! | lenia_frame/64x64/full/r13                  | 11.5 ms  |
+//! | lenia_frame/64x64/skip_empty/r13            | 11.4 ms  |
+//! | lenia_frame/128x128/full/r5                 | 6.31 ms  |
+//! | lenia_frame/128x128/skip_empty/r5           | 6.36 ms  |
+//! | lenia_frame/128x128/full/r13                | 45.0 ms  |
+//! | lenia_frame/128x128/skip_empty/r13          | 45.1 ms  |
+//! | lenia_sparse_frame/full/128x128/r13         | 46.4 ms  |
+//! | lenia_sparse_frame/skip_empty/128x128/r13   | 25.2 ms  |
+//! | gol_frame/active_cells/128x128              | 1.49 ms  |
+//! | gol_frame/full_scan/128x128                 | 1.76 ms  |
+//! | gol_frame/active_cells/512x512              | 31.1 ms  |
+//! | gol_frame/full_scan/512x512                 | 31.8 ms  |
+//!
+//! Skipping empty regions costs nothing on a soup and saves the empty part of a sparse grid.
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use lenia::{Lenia, Mode};
+use std::hint::black_box;
+
+/// grid of `size`x`size` cells, fully seeded
+fn seeded_lenia(size: usize, mode: Mode, kernel_radius: Option<usize>) -> Lenia {
+    fastrand::seed(42);
+    Lenia::new(
+        (size, size),
+        Some(((0, size - 1), (0, size - 1))),
+        Some(mode),
+        None,
+        kernel_radius,
+    )
+}
+
+fn lenia_frame(c: &mut Criterion) {
+    let mut group = c.benchmark_group("lenia_frame");
+    group.sample_size(10);
+    for size in [32, 64, 128] {
+        for kernel_radius in [5, 13] {
+            // a fresh soup every step: a seeded grid dies out within a few steps, and skipping
+            // would then only measure an empty grid
+            let soup = |skip_empty_regions: bool| {
+                let mut lenia = seeded_lenia(size, Mode::Lenia, Some(kernel_radius));
+                lenia.skip_empty_regions = skip_empty_regions;
+                lenia
+            };
+            for (name, skip_empty_regions) in [("full", false), ("skip_empty", true)] {
+                group.bench_function(
+                    BenchmarkId::new(format!("{size}x{size}/{name}"), format!("r{kernel_radius}")),
+                    |b| {
+                        b.iter_batched_ref(
+                            || soup(skip_empty_regions),
+                            |lenia| black_box(lenia).compute_next_lenia_frame(),
+                            BatchSize::LargeInput,
+                        )
+                    },
+                );
+            }
+        }
+    }
+    group.finish();
+}
+
+/// a quarter of the grid seeded in a corner, the rest empty, where skipping empty regions pays
+fn lenia_sparse_frame(c: &mut Criterion) {
+    let mut group = c.benchmark_group("lenia_sparse_frame");
+    group.sample_size(10);
+    let size = 128;
+    let kernel_radius = 13;
+    let patch = |skip_empty_regions: bool| {
+        fastrand::seed(42);
+        let mut lenia = Lenia::new(
+            (size, size),
+            Some(((0, size / 2 - 1), (0, size / 2 - 1))),
+            Some(Mode::Lenia),
+            None,
+            Some(kernel_radius),
+        );
+        lenia.skip_empty_regions = skip_empty_regions;
+        lenia
+    };
+    for (name, skip_empty_regions) in [("full", false), ("skip_empty", true)] {
+        group.bench_function(
+            BenchmarkId::new(name, format!("{size}x{size}/r{kernel_radius}")),
+            |b| {
+                b.iter_batched_ref(
+                    || patch(skip_empty_regions),
+                    |lenia| black_box(lenia).compute_next_lenia_frame(),
+                    BatchSize::LargeInput,
+                )
+            },
+        );
+    }
+    group.finish();
+}
+
+fn gol_frame(c: &mut Criterion) {
+    let mut group = c.benchmark_group("gol_frame");
+    for size in [128, 512] {
+        // a random soup is still busy after 10 generations, so both variants measure a lively grid
+        let busy_soup = |use_active_cells: bool| {
+            let mut lenia = seeded_lenia(size, Mode::GameOfLife, None);
+            lenia.run(10);
+            lenia.scan_all_cells = !use_active_cells;
+            lenia
+        };
+        for (name, use_active_cells) in [("active_cells", true), ("full_scan", false)] {
+            group.bench_function(BenchmarkId::new(name, format!("{size}x{size}")), |b| {
+                b.iter_batched_ref(
+                    || busy_soup(use_active_cells),
+                    |lenia| lenia.compute_next_gol_frame(),
+                    BatchSize::LargeInput,
+                )
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, lenia_frame, lenia_sparse_frame, gol_frame);
+criterion_main!(benches);
@@ -0,0 +1,219 @@
+//! named, validated construction of a Lenia instance, instead of the positional `Lenia::new`
+
+use crate::{Boundary, Lenia, Mode, MIN_GRID_SIZE};
+
+/// every setting is optional: a 128x128 classic Lenia soup covering the whole grid by default
+#[derive(Default)]
+pub struct LeniaBuilder {
+    dimensions: Option<(usize, usize)>,
+    mode: Option<Mode>,
+    delta_t: Option<f64>,
+    kernel_radius: Option<(usize, usize)>,
+    seed: Option<u64>,
+    spawn_area: Option<((usize, usize), (usize, usize))>,
+    seed_blobs: Option<usize>,
+    boundary: Option<Boundary>,
+}
+
+impl LeniaBuilder {
+    /// (width, height) in cells
+    pub fn dimensions(mut self, wcell_count: usize, hcell_count: usize) -> Self {
+        self.dimensions = Some((wcell_count, hcell_count));
+        self
+    }
+
+    pub fn mode(mut self, mode: Mode) -> Self {
+        self.mode = Some(mode);
+        self
+    }
+
+    pub fn delta_t(mut self, delta_t: f64) -> Self {
+        self.delta_t = Some(delta_t);
+        self
+    }
+
+    /// the same radius on both axes, see [`LeniaBuilder::elliptical_kernel_radius`]
+    pub fn kernel_radius(self, radius: usize) -> Self {
+        self.elliptical_kernel_radius(radius, radius)
+    }
+
+    /// (horizontal, vertical) in cells width
+    pub fn elliptical_kernel_radius(mut self, radius_x: usize, radius_y: usize) -> Self {
+        self.kernel_radius = Some((radius_x, radius_y));
+        self
+    }
+
+    /// the instance draws from its own generator seeded with `seed`, instead of one derived from
+    /// the global generator
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// ((area_w_min, area_w_max), (area_h_min, area_h_max)), bounds included
+    pub fn spawn_area(mut self, area: ((usize, usize), (usize, usize))) -> Self {
+        self.spawn_area = Some(area);
+        self
+    }
+
+    /// randomize this many random areas instead of the spawn area, see [`Lenia::seed_blobs`]
+    pub fn seed_blobs(mut self, count: usize) -> Self {
+        self.seed_blobs = Some(count);
+        self
+    }
+
+    /// the edges that wrap around, a torus by default
+    pub fn boundary(mut self, boundary: Boundary) -> Self {
+        self.boundary = Some(boundary);
+        self
+    }
+
+    pub fn build(self) -> Result<Lenia, String> {
+        let (wcell_count, hcell_count) = self.dimensions.unwrap_or((128, 128));
+        if wcell_count < MIN_GRID_SIZE || hcell_count < MIN_GRID_SIZE {
+            return Err(format!(
+                "dimensions must be at least {MIN_GRID_SIZE}x{MIN_GRID_SIZE}, got {wcell_count}x{hcell_count}"
+            ));
+        }
+        if let Some(delta_t) = self
+            .delta_t
+            .filter(|delta_t| !delta_t.is_finite() || *delta_t <= 0.0)
+        {
+            return Err(format!("delta_t must be positive, got {delta_t}"));
+        }
+        if let Some((radius_x, radius_y)) = self.kernel_radius.filter(|&(x, y)| x == 0 || y == 0) {
+            return Err(format!(
+                "kernel radius must be at least 1, got {radius_x}x{radius_y}"
+            ));
+        }
+        let spawn_area = self
+            .spawn_area
+            .unwrap_or(((0, wcell_count - 1), (0, hcell_count - 1)));
+        let ((area_w_min, area_w_max), (area_h_min, area_h_max)) = spawn_area;
+        if area_w_min > area_w_max || area_h_min > area_h_max {
+            return Err(format!(
+                "spawn area bounds must be (min, max), got {spawn_area:?}"
+            ));
+        }
+
+        match self.seed_blobs {
+            Some(0) => return Err("seed_blobs must be at least 1".to_string()),
+            Some(_) if self.spawn_area.is_some() => {
+                return Err("seed_blobs replaces the spawn area, set only one of them".to_string())
+            }
+            _ => (),
+        }
+
+        let rng = match self.seed {
+            Some(seed) => fastrand::Rng::with_seed(seed),
+            None => fastrand::Rng::new(),
+        };
+        let mut lenia = Lenia::with_rng(
+            (wcell_count, hcell_count),
+            Some(spawn_area),
+            self.mode,
+            self.delta_t,
+            None,
+            rng,
+        );
+        if let Some(kernel_radius) = self.kernel_radius {
+            lenia.kernel_radius = kernel_radius;
+        }
+        if let Some(boundary) = self.boundary {
+            lenia.boundary = boundary;
+        }
+        if let Some(count) = self.seed_blobs {
+            lenia.seed_blobs(count);
+        }
+        Ok(lenia)
+    }
+}
+
+impl Lenia {
+    pub fn builder() -> LeniaBuilder {
+        LeniaBuilder::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Topology, DEFAULT_DELTA_T, DEFAULT_KERNEL_RADIUS};
+
+    #[test]
+    fn default_build_is_a_lenia_soup_over_the_whole_grid() {
+        let lenia = Lenia::builder().seed(1).build().unwrap();
+        assert_eq!((lenia.cells[0].len(), lenia.cells.len()), (128, 128));
+        assert!(matches!(lenia.mode, Mode::Lenia));
+        assert_eq!(lenia.delta_t, DEFAULT_DELTA_T);
+        assert_eq!(
+            lenia.kernel_radius,
+            (DEFAULT_KERNEL_RADIUS, DEFAULT_KERNEL_RADIUS)
+        );
+        assert_eq!(lenia.boundary, Boundary::default());
+        let is_alive = |raw: &Vec<_>| raw.iter().any(|&cell| cell > 0.0);
+        assert!(is_alive(&lenia.cells[0]) && is_alive(&lenia.cells[127]));
+    }
+
+    #[test]
+    fn fully_specified_build_has_every_setting() {
+        let build = || {
+            Lenia::builder()
+                .dimensions(40, 30)
+                .mode(Mode::GameOfLife)
+                .delta_t(0.5)
+                .elliptical_kernel_radius(4, 6)
+                .seed(9)
+                .spawn_area(((10, 19), (5, 9)))
+                .boundary(Topology::KleinBottle.boundary())
+                .build()
+                .unwrap()
+        };
+        let lenia = build();
+        assert_eq!((lenia.cells[0].len(), lenia.cells.len()), (40, 30));
+        assert!(matches!(lenia.mode, Mode::GameOfLife));
+        assert_eq!(lenia.delta_t, 0.5);
+        assert_eq!(lenia.kernel_radius, (4, 6));
+        assert_eq!(lenia.boundary, Topology::KleinBottle.boundary());
+        for (raw, cells) in lenia.cells.iter().enumerate() {
+            for (col, &cell) in cells.iter().enumerate() {
+                if !((5..=9).contains(&raw) && (10..=19).contains(&col)) {
+                    assert_eq!(cell, 0.0, "({raw}, {col}) is outside the spawn area");
+                }
+            }
+        }
+        assert!(lenia.cells.iter().flatten().any(|&cell| cell == 1.0));
+        // the seed draws the same soup
+        assert_eq!(build().cells, lenia.cells);
+    }
+
+    #[test]
+    fn build_rejects_invalid_settings() {
+        let invalid_builds = [
+            Lenia::builder().dimensions(0, 0),
+            Lenia::builder().dimensions(64, 0),
+            Lenia::builder().dimensions(MIN_GRID_SIZE - 1, 64),
+            Lenia::builder().delta_t(0.0),
+            Lenia::builder().delta_t(f64::NAN),
+            Lenia::builder().kernel_radius(0),
+            Lenia::builder().elliptical_kernel_radius(3, 0),
+            Lenia::builder().spawn_area(((8, 4), (0, 10))),
+            Lenia::builder().seed_blobs(0),
+            Lenia::builder()
+                .seed_blobs(3)
+                .spawn_area(((0, 10), (0, 10))),
+        ];
+        for (index, builder) in invalid_builds.into_iter().enumerate() {
+            assert!(builder.build().is_err(), "build {index}");
+        }
+        assert_eq!(
+            Lenia::builder().dimensions(0, 0).build().err(),
+            Some("dimensions must be at least 2x2, got 0x0".to_string())
+        );
+        // the smallest grid is fine
+        assert!(Lenia::builder()
+            .dimensions(MIN_GRID_SIZE, MIN_GRID_SIZE)
+            .build()
+            .is_ok());
+    }
+}
@@ -0,0 +1,185 @@
+//! Line-based commands read from stdin, to drive the simulation from a script
+
+use std::{
+    io::BufRead,
+    path::PathBuf,
+    str::FromStr,
+    sync::mpsc::{channel, Receiver},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    /// `step N`: run N steps, then halt
+    Step(usize),
+    /// `start`
+    Start,
+    /// `pause`
+    Pause,
+    /// `reseed`: randomize a random area, like the reset key
+    Reseed,
+    /// `clear`
+    Clear,
+    /// `set <parameter> <value>`, the value being a valid one for the parameter
+    Set(Parameter, f64),
+    /// `save <path>`: the cells as a .npy file, or the live cells as Golly RLE for a .rle path
+    /// (and macrocell for a .mc one with the `macrocell` feature)
+    Save(PathBuf),
+}
+
+/// what `set` can tune, the growth parameters being the ones of the first kernel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Parameter {
+    DeltaT,
+    Mu,
+    Sigma,
+    /// sets both the horizontal and vertical radii
+    KernelRadius,
+    KernelAlpha,
+}
+
+impl FromStr for Parameter {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "dt" | "delta_t" => Ok(Self::DeltaT),
+            "mu" => Ok(Self::Mu),
+            "sigma" => Ok(Self::Sigma),
+            "radius" | "kernel_radius" => Ok(Self::KernelRadius),
+            "alpha" | "kernel_alpha" => Ok(Self::KernelAlpha),
+            _ => Err(format!(
+                "unknown parameter \"{name}\", expected dt, mu, sigma, radius or alpha"
+            )),
+        }
+    }
+}
+
+impl Parameter {
+    /// `value` when it's one the parameter can take, whole for the radius and positive for all
+    fn check(self, value: f64) -> Result<f64, String> {
+        match self {
+            _ if !value.is_finite() || value <= 0.0 => {
+                Err(format!("invalid value {value}, expected a positive number"))
+            }
+            Self::Mu if value > 1.0 => Err(format!("invalid mu {value}, expected at most 1")),
+            Self::KernelRadius if value.fract() != 0.0 => Err(format!(
+                "invalid radius {value}, expected a whole number of at least 1"
+            )),
+            _ => Ok(value),
+        }
+    }
+}
+
+impl FromStr for Command {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        fn number<T: FromStr>(value: Option<&str>, usage: &str) -> Result<T, String> {
+            value
+                .and_then(|value| value.parse().ok())
+                .ok_or_else(|| format!("expected {usage}"))
+        }
+
+        let mut words = line.split_whitespace();
+        let command = match words.next() {
+            Some("step") => Self::Step(number(words.next(), "step <number of steps>")?),
+            Some("start") => Self::Start,
+            Some("pause") => Self::Pause,
+            Some("reseed") => Self::Reseed,
+            Some("clear") => Self::Clear,
+            Some("set") => {
+                let parameter = words
+                    .next()
+                    .ok_or("expected set <parameter> <value>")?
+                    .parse()?;
+                let value = number(words.next(), "set <parameter> <value>")?;
+                Self::Set(parameter, Parameter::check(parameter, value)?)
+            }
+            Some("save") => Self::Save(words.next().ok_or("expected save <path>")?.into()),
+            Some(command) => return Err(format!("unknown command \"{command}\"")),
+            None => return Err("empty command".to_string()),
+        };
+        match words.next() {
+            Some(extra) => Err(format!("unexpected \"{extra}\" after the command")),
+            None => Ok(command),
+        }
+    }
+}
+
+/// read commands from stdin on their own thread, reporting malformed lines on stderr; the
+/// channel closes with stdin
+pub fn spawn_stdin_reader() -> Receiver<Command> {
+    let (sender, commands) = channel();
+    std::thread::spawn(move || {
+        for line in std::io::stdin().lock().lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            match line.parse() {
+                Ok(command) => {
+                    if sender.send(command).is_err() {
+                        break;
+                    }
+                }
+                Err(err) => eprintln!("invalid command \"{line}\": {err}"),
+            }
+        }
+    });
+    commands
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commands_parse_from_their_lines() {
+        assert_eq!("step 5".parse(), Ok(Command::Step(5)));
+        assert_eq!(" pause ".parse(), Ok(Command::Pause));
+        assert_eq!("set mu 0.3".parse(), Ok(Command::Set(Parameter::Mu, 0.3)));
+        assert_eq!(
+            "set radius 20".parse(),
+            Ok(Command::Set(Parameter::KernelRadius, 20.0))
+        );
+        assert_eq!("save out.rle".parse(), Ok(Command::Save("out.rle".into())));
+        for invalid in [
+            "",
+            "step",
+            "step -1",
+            "pause now",
+            "jump",
+            "set mu",
+            "set speed 2",
+        ] {
+            assert!(invalid.parse::<Command>().is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn set_rejects_values_its_parameter_cannot_take() {
+        for invalid in [
+            "set dt 0",
+            "set dt -0.1",
+            "set dt NaN",
+            "set dt inf",
+            "set mu 1.5",
+            "set sigma 0",
+            "set alpha -2",
+            "set radius 0",
+            "set radius 2.5",
+        ] {
+            assert!(invalid.parse::<Command>().is_err(), "{invalid}");
+        }
+        assert_eq!(
+            "set sigma -1".parse::<Command>(),
+            Err("invalid value -1, expected a positive number".to_string())
+        );
+        assert_eq!(
+            "set radius 2.5".parse::<Command>(),
+            Err("invalid radius 2.5, expected a whole number of at least 1".to_string())
+        );
+    }
+}
@@ -0,0 +1,556 @@
+//! Routing of the window events to the simulation, apart from the window itself so that it
+//! runs the same on replays and in tests
+
+use crate::{
+    apply_action, keymap::Action, keymap::Keymap, paint_at_cursor, replay::ReplayEventKind,
+    save_preset, AppState, STABLE_CELL_CHANGE,
+};
+use lenia::Lenia;
+use piston_window::{Button, ButtonArgs, ButtonState, Input, Key, Loop, Motion, MouseButton};
+
+/// what an input asks of the window, see [`handle_input`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowRequest {
+    Close,
+    /// frames stop waiting for the display while benchmarking
+    BenchMode(bool),
+}
+
+/// what the main loop does after a loop event, see [`handle_loop`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoopOutcome {
+    /// draw the frame, on render events only
+    Draw,
+    /// the update reached `--max-frames`
+    ReachedMaxFrames,
+    Idle,
+}
+
+/// update events advance the simulation, render events leave it as it is to draw it
+pub fn handle_loop(event: &Loop, app_state: &mut AppState, lenia: &mut Lenia) -> LoopOutcome {
+    match event {
+        Loop::Update(_) if update(app_state, lenia) => LoopOutcome::ReachedMaxFrames,
+        Loop::Render(_) => LoopOutcome::Draw,
+        _ => LoopOutcome::Idle,
+    }
+}
+
+/// the character of a single keystroke, `None` for the empty text events IMEs can deliver and
+/// for pastes of several characters at once, which aren't commands
+fn single_character(text: &str) -> Option<char> {
+    let mut chars = text.chars();
+    match (chars.next(), chars.next()) {
+        (Some(character), None) => Some(character),
+        _ => None,
+    }
+}
+
+/// react to a live or replayed input event, telling what the window should do about it
+pub fn handle_input(
+    input: &Input,
+    app_state: &mut AppState,
+    lenia: &mut Lenia,
+    keymap: &Keymap,
+) -> Option<WindowRequest> {
+    match input {
+        Input::Move(Motion::MouseCursor(cursor)) => {
+            app_state.cursor = Some(*cursor);
+            app_state.hovered_cell = app_state.screen_to_grid(lenia, cursor[0], cursor[1]);
+            paint_at_cursor(app_state, lenia);
+        }
+        Input::Cursor(false) => app_state.hovered_cell = None,
+        Input::Move(Motion::MouseScroll([_, dy])) => app_state.scroll(lenia, *dy),
+        Input::Text(text) if app_state.preset_name_input.is_some() => {
+            if let Some(name) = &mut app_state.preset_name_input {
+                name.extend(text.chars().filter(|c| !c.is_control()));
+            }
+        }
+        Input::Button(ButtonArgs {
+            state: ButtonState::Press,
+            button: Button::Keyboard(key @ (Key::Return | Key::Escape | Key::Backspace)),
+            ..
+        }) if app_state.preset_name_input.is_some() => match key {
+            Key::Return => save_preset(app_state, lenia),
+            Key::Escape => app_state.preset_name_input = None,
+            _ => {
+                if let Some(name) = &mut app_state.preset_name_input {
+                    name.pop();
+                }
+            }
+        },
+        Input::Text(text) => {
+            // only single keystrokes are commands
+            let action = keymap.action(single_character(text)?)?;
+            apply_action(action, app_state, lenia);
+            // in benchmark mode frames stop waiting for the display, so they come as fast as
+            // computed
+            return Some(WindowRequest::BenchMode(app_state.benchmark.is_some()));
+        }
+        Input::Button(ButtonArgs {
+            state: ButtonState::Press,
+            button: Button::Keyboard(Key::F1),
+            ..
+        }) => apply_action(Action::ToggleHelp, app_state, lenia),
+        Input::Button(ButtonArgs {
+            state: ButtonState::Press,
+            button: Button::Keyboard(Key::Escape),
+            ..
+        }) if app_state.show_help => app_state.show_help = false,
+        Input::Button(ButtonArgs {
+            state: ButtonState::Press,
+            button: Button::Keyboard(Key::Escape),
+            ..
+        }) => return Some(WindowRequest::Close),
+        Input::Button(ButtonArgs {
+            state,
+            button: Button::Keyboard(Key::LShift | Key::RShift),
+            ..
+        }) => app_state.is_painting_walls = *state == ButtonState::Press,
+        Input::Button(ButtonArgs {
+            state: ButtonState::Press,
+            button: Button::Keyboard(key),
+            ..
+        }) if app_state.is_game_paused && !app_state.is_drawing => match key {
+            Key::Left => lenia.shift(-1, 0),
+            Key::Right => lenia.shift(1, 0),
+            Key::Up => lenia.shift(0, -1),
+            Key::Down => lenia.shift(0, 1),
+            _ => (),
+        },
+        Input::Button(ButtonArgs {
+            state,
+            button: Button::Mouse(button @ (MouseButton::Left | MouseButton::Right)),
+            ..
+        }) => {
+            app_state.held_mouse_button = (*state == ButtonState::Press).then_some(*button);
+            // a press starts a new stroke, not joined to the previous one
+            app_state.last_painted_cell = None;
+            // a single click places a dab, dragging paints a stroke
+            paint_at_cursor(app_state, lenia);
+        }
+        _ => (),
+    }
+    None
+}
+
+/// replay a recorded event as it was handled live
+pub fn handle_replay_event(
+    kind: &ReplayEventKind,
+    app_state: &mut AppState,
+    lenia: &mut Lenia,
+    keymap: &Keymap,
+) -> Option<WindowRequest> {
+    match kind {
+        ReplayEventKind::Input(input) => handle_input(input, app_state, lenia, keymap),
+        ReplayEventKind::GridSize(w, h) => {
+            lenia.resize(*w, *h);
+            None
+        }
+    }
+}
+
+/// advance the simulation on an update event unless paused, stopping short and pausing once the
+/// steps queued by [`crate::keymap::Action::Advance`] are done, returning whether it reached
+/// `--max-frames`
+pub fn update(app_state: &mut AppState, lenia: &mut Lenia) -> bool {
+    if app_state.is_game_paused {
+        return false;
+    }
+    let mut steps = app_state
+        .pending_steps
+        .map_or(app_state.compute_per_render, |pending| {
+            pending.min(app_state.compute_per_render)
+        });
+    if let Some(max_frames) = app_state.max_frames {
+        steps = steps.min(max_frames.saturating_sub(lenia.generation) as usize);
+    }
+    lenia.run(steps);
+    app_state.frames_since_render += 1;
+    if let Some(pending) = app_state.pending_steps {
+        app_state.pending_steps = Some(pending - steps).filter(|&left| left > 0);
+        app_state.is_game_paused = app_state.pending_steps.is_none();
+    }
+    let has_reached_max_frames = app_state
+        .max_frames
+        .is_some_and(|max_frames| lenia.generation >= max_frames);
+    if has_reached_max_frames {
+        println!(
+            "reached frame {}, total mass {}",
+            lenia.generation,
+            lenia.total_mass()
+        );
+        app_state.is_game_paused = true;
+        app_state.pending_steps = None;
+    }
+    if let Some(auto_pause_frames) = app_state.auto_pause_frames {
+        let is_stable = lenia
+            .last_step_change()
+            .is_some_and(|change| change < STABLE_CELL_CHANGE);
+        app_state.stable_frames = if is_stable {
+            app_state.stable_frames + 1
+        } else {
+            0
+        };
+        if app_state.stable_frames >= auto_pause_frames {
+            let reason = if lenia.total_mass() == 0.0 {
+                "every cell died"
+            } else {
+                "the grid stopped changing"
+            };
+            println!(
+                "paused at generation {}: {reason} {auto_pause_frames} frames ago",
+                lenia.generation
+            );
+            app_state.is_game_paused = true;
+            app_state.pending_steps = None;
+            app_state.stable_frames = 0;
+        }
+    }
+    has_reached_max_frames
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BrushShape;
+    use lenia::{Cell, Mode, Neighbourhood, Symmetry};
+    use piston_window::{RenderArgs, UpdateArgs};
+
+    const UPDATE: Loop = Loop::Update(UpdateArgs { dt: 1.0 / 60.0 });
+    const RENDER: Loop = Loop::Render(RenderArgs {
+        ext_dt: 0.0,
+        window_size: [256.0, 256.0],
+        draw_size: [256, 256],
+    });
+
+    fn running_life() -> (AppState, Lenia) {
+        // its own generator, the global one being shared with the tests running alongside
+        let lenia = Lenia::builder()
+            .dimensions(32, 32)
+            .mode(Mode::GameOfLife)
+            .seed(42)
+            .build()
+            .unwrap();
+        let app_state = AppState {
+            is_game_paused: false,
+            ..AppState::default()
+        };
+        (app_state, lenia)
+    }
+
+    #[test]
+    fn update_events_step_the_simulation_and_render_events_draw_it() {
+        let (mut app_state, mut lenia) = running_life();
+        assert_eq!(
+            handle_loop(&UPDATE, &mut app_state, &mut lenia),
+            LoopOutcome::Idle
+        );
+        assert_eq!(lenia.generation, 1);
+
+        let cells = lenia.cells.clone();
+        assert_eq!(
+            handle_loop(&RENDER, &mut app_state, &mut lenia),
+            LoopOutcome::Draw
+        );
+        assert_eq!(lenia.generation, 1);
+        assert_eq!(lenia.cells, cells);
+
+        app_state.compute_per_render = 3;
+        handle_loop(&UPDATE, &mut app_state, &mut lenia);
+        assert_eq!(lenia.generation, 4);
+        app_state.is_game_paused = true;
+        handle_loop(&UPDATE, &mut app_state, &mut lenia);
+        assert_eq!(lenia.generation, 4);
+    }
+
+    #[test]
+    fn updates_stop_at_max_frames() {
+        let (mut app_state, mut lenia) = running_life();
+        app_state.compute_per_render = 4;
+        app_state.max_frames = Some(6);
+        assert_eq!(
+            handle_loop(&UPDATE, &mut app_state, &mut lenia),
+            LoopOutcome::Idle
+        );
+        assert_eq!(
+            handle_loop(&UPDATE, &mut app_state, &mut lenia),
+            LoopOutcome::ReachedMaxFrames
+        );
+        assert_eq!(lenia.generation, 6);
+        assert!(app_state.is_game_paused);
+    }
+
+    #[test]
+    fn only_single_characters_are_commands() {
+        assert_eq!(single_character("é"), Some('é'));
+        let (mut app_state, mut lenia) = running_life();
+        let keymap = Keymap::default();
+        let cells = lenia.cells.clone();
+        // "c" clears, but not within a paste, and empty IME events do nothing
+        for text in ["", "cc", "ch", "\u{301}c"] {
+            let input = Input::Text(text.to_string());
+            assert_eq!(
+                handle_input(&input, &mut app_state, &mut lenia, &keymap),
+                None
+            );
+            assert_eq!(lenia.cells, cells, "{text:?}");
+            assert!(!app_state.is_game_paused, "{text:?}");
+        }
+        let input = Input::Text("h".to_string());
+        assert_eq!(
+            handle_input(&input, &mut app_state, &mut lenia, &keymap),
+            Some(WindowRequest::BenchMode(false))
+        );
+        assert!(app_state.is_game_paused);
+        // unbound characters aren't anything either
+        let input = Input::Text("~".to_string());
+        assert_eq!(
+            handle_input(&input, &mut app_state, &mut lenia, &keymap),
+            None
+        );
+    }
+
+    #[test]
+    fn escape_closes_the_window_unless_naming_a_preset_or_reading_the_help() {
+        let (mut app_state, mut lenia) = running_life();
+        let keymap = Keymap::default();
+        let escape = Input::Button(ButtonArgs {
+            state: ButtonState::Press,
+            button: Button::Keyboard(Key::Escape),
+            scancode: None,
+        });
+        app_state.preset_name_input = Some("glider".to_string());
+        assert_eq!(
+            handle_input(&escape, &mut app_state, &mut lenia, &keymap),
+            None
+        );
+        assert_eq!(app_state.preset_name_input, None);
+        let f1 = Input::Button(ButtonArgs {
+            state: ButtonState::Press,
+            button: Button::Keyboard(Key::F1),
+            scancode: None,
+        });
+        handle_input(&f1, &mut app_state, &mut lenia, &keymap);
+        assert!(app_state.show_help);
+        assert_eq!(
+            handle_input(&escape, &mut app_state, &mut lenia, &keymap),
+            None
+        );
+        assert!(!app_state.show_help);
+        assert_eq!(
+            handle_input(&escape, &mut app_state, &mut lenia, &keymap),
+            Some(WindowRequest::Close)
+        );
+    }
+
+    #[test]
+    fn actions_change_what_they_name() {
+        let (mut app_state, mut lenia) = running_life();
+        app_state.is_game_paused = true;
+        apply_action(Action::Start, &mut app_state, &mut lenia);
+        assert!(!app_state.is_game_paused);
+        apply_action(Action::Halt, &mut app_state, &mut lenia);
+        assert!(app_state.is_game_paused);
+
+        // every flag toggles back and forth
+        for action in [
+            Action::ToggleBrushShape,
+            Action::ToggleQuantizedRender,
+            Action::ToggleRuler,
+            Action::ToggleDiffView,
+            Action::ToggleHelp,
+        ] {
+            let flag = |app_state: &AppState| match action {
+                Action::ToggleBrushShape => app_state.brush_shape == BrushShape::Disc,
+                Action::ToggleRuler => app_state.show_ruler,
+                Action::ToggleDiffView => app_state.show_diff,
+                Action::ToggleHelp => app_state.show_help,
+                _ => app_state.is_render_quantized,
+            };
+            let before = flag(&app_state);
+            apply_action(action, &mut app_state, &mut lenia);
+            assert_eq!(flag(&app_state), !before, "{action:?}");
+            apply_action(action, &mut app_state, &mut lenia);
+            assert_eq!(flag(&app_state), before, "{action:?}");
+        }
+
+        lenia.walls.insert((3, 3));
+        apply_action(Action::Clear, &mut app_state, &mut lenia);
+        assert!(lenia.cells.iter().flatten().all(|&cell| cell == 0.0));
+        assert!(lenia.walls.is_empty());
+
+        app_state.brush_radius = 0;
+        apply_action(Action::ShrinkBrush, &mut app_state, &mut lenia);
+        assert_eq!(app_state.brush_radius, 0);
+        apply_action(Action::GrowBrush, &mut app_state, &mut lenia);
+        assert_eq!(app_state.brush_radius, 1);
+        apply_action(Action::RaisePaintIntensity, &mut app_state, &mut lenia);
+        assert_eq!(app_state.paint_intensity, 1.0);
+        apply_action(Action::LowerPaintIntensity, &mut app_state, &mut lenia);
+        assert!((app_state.paint_intensity - 0.9).abs() < 1e-6);
+
+        app_state.compute_per_render = 1;
+        apply_action(Action::FewerStepsPerFrame, &mut app_state, &mut lenia);
+        assert_eq!(app_state.compute_per_render, 1);
+        apply_action(Action::MoreStepsPerFrame, &mut app_state, &mut lenia);
+        assert_eq!(app_state.compute_per_render, 2);
+
+        let cells = lenia.cells.clone();
+        apply_action(Action::Invert, &mut app_state, &mut lenia);
+        assert!(lenia
+            .cells
+            .iter()
+            .flatten()
+            .zip(cells.iter().flatten())
+            .all(|(&inverted, &cell)| inverted == 1.0 - cell));
+
+        // advancing runs the queued steps, starting or halting drops them
+        app_state.advance_steps = 5;
+        apply_action(Action::Advance, &mut app_state, &mut lenia);
+        assert!(!app_state.is_game_paused);
+        assert_eq!(app_state.pending_steps, Some(5));
+        apply_action(Action::Halt, &mut app_state, &mut lenia);
+        assert_eq!(app_state.pending_steps, None);
+        apply_action(Action::Advance, &mut app_state, &mut lenia);
+        apply_action(Action::Start, &mut app_state, &mut lenia);
+        assert_eq!(app_state.pending_steps, None);
+
+        apply_action(Action::ToggleMode, &mut app_state, &mut lenia);
+        assert!(matches!(lenia.mode, Mode::Lenia));
+        apply_action(Action::ToggleMode, &mut app_state, &mut lenia);
+        assert!(matches!(lenia.mode, Mode::GameOfLife));
+
+        apply_action(Action::ToggleVonNeumann, &mut app_state, &mut lenia);
+        assert_eq!(lenia.neighbourhood, Neighbourhood::VonNeumann);
+        apply_action(Action::ToggleVonNeumann, &mut app_state, &mut lenia);
+        assert_eq!(lenia.neighbourhood, Neighbourhood::Moore);
+    }
+
+    #[test]
+    fn running_and_drawing_are_toggled_apart() {
+        let keymap = Keymap::default();
+        let press = |key: char, app_state: &mut AppState, lenia: &mut Lenia| {
+            handle_input(&Input::Text(key.to_string()), app_state, lenia, &keymap);
+        };
+        // every sequence of 3 presses of d, s and h from a paused grid without drawing
+        for sequence in 0..27 {
+            let (mut app_state, mut lenia) = running_life();
+            app_state.is_game_paused = true;
+            let (mut is_running, mut is_drawing) = (false, false);
+            let keys: Vec<char> = [sequence / 9, sequence / 3 % 3, sequence % 3]
+                .into_iter()
+                .map(|key| ['d', 's', 'h'][key])
+                .collect();
+            for &key in &keys {
+                press(key, &mut app_state, &mut lenia);
+                match key {
+                    'd' => is_drawing = !is_drawing,
+                    's' => is_running = true,
+                    _ => is_running = false,
+                }
+                assert_eq!(
+                    (!app_state.is_game_paused, app_state.is_drawing),
+                    (is_running, is_drawing),
+                    "{keys:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn clicks_only_paint_in_draw_mode() {
+        let (mut app_state, mut lenia) = running_life();
+        app_state.is_game_paused = true;
+        lenia.clear();
+        let keymap = Keymap::default();
+        let click = |state, app_state: &mut AppState, lenia: &mut Lenia| {
+            let button = Input::Button(ButtonArgs {
+                state,
+                button: Button::Mouse(MouseButton::Left),
+                scancode: None,
+            });
+            handle_input(&button, app_state, lenia, &keymap);
+        };
+        let cursor = Input::Move(Motion::MouseCursor([21.0, 11.0]));
+        handle_input(&cursor, &mut app_state, &mut lenia, &keymap);
+        assert_eq!(app_state.hovered_cell, Some((5, 10)));
+
+        click(ButtonState::Press, &mut app_state, &mut lenia);
+        click(ButtonState::Release, &mut app_state, &mut lenia);
+        assert!(lenia.cells.iter().flatten().all(|&cell| cell == 0.0));
+
+        handle_input(
+            &Input::Text("d".to_string()),
+            &mut app_state,
+            &mut lenia,
+            &keymap,
+        );
+        click(ButtonState::Press, &mut app_state, &mut lenia);
+        click(ButtonState::Release, &mut app_state, &mut lenia);
+        assert_eq!(lenia.cells[5][10], 1.0);
+        assert_eq!(lenia.cells.iter().flatten().sum::<Cell>(), 1.0);
+        // released, the cursor moves without painting
+        let cursor = Input::Move(Motion::MouseCursor([1.0, 1.0]));
+        handle_input(&cursor, &mut app_state, &mut lenia, &keymap);
+        assert_eq!(lenia.cells[0][0], 0.0);
+        // the placed seed runs right away, still in draw mode
+        handle_input(
+            &Input::Text("s".to_string()),
+            &mut app_state,
+            &mut lenia,
+            &keymap,
+        );
+        assert!(!app_state.is_game_paused && app_state.is_drawing);
+    }
+
+    #[test]
+    fn symmetric_soups_get_mirrored_brush_strokes() {
+        let (mut app_state, mut lenia) = running_life();
+        lenia.clear();
+        app_state.is_drawing = true;
+        app_state.brush_shape = BrushShape::Disc;
+        app_state.brush_radius = 2;
+        app_state.seed_symmetry = Some(Symmetry::Quadrants);
+        let keymap = Keymap::default();
+        for input in [
+            Input::Move(Motion::MouseCursor([21.0, 11.0])),
+            Input::Button(ButtonArgs {
+                state: ButtonState::Press,
+                button: Button::Mouse(MouseButton::Left),
+                scancode: None,
+            }),
+        ] {
+            handle_input(&input, &mut app_state, &mut lenia, &keymap);
+        }
+        for (raw, col) in [(5, 10), (5, 21), (26, 10), (26, 21)] {
+            assert_eq!(lenia.cells[raw][col], 1.0, "({raw}, {col})");
+        }
+        let mirrored_x: Vec<Vec<_>> = lenia
+            .cells
+            .iter()
+            .map(|cells| cells.iter().rev().copied().collect())
+            .collect();
+        assert_eq!(mirrored_x, lenia.cells);
+        let mirrored_y: Vec<_> = lenia.cells.iter().rev().cloned().collect();
+        assert_eq!(mirrored_y, lenia.cells);
+        // 4 discs of 13 cells
+        let live_cells = lenia.cells.iter().flatten().filter(|&&cell| cell == 1.0);
+        assert_eq!(live_cells.count(), 4 * 13);
+    }
+
+    #[test]
+    fn advancing_runs_exactly_its_steps_then_pauses() {
+        let (mut app_state, mut lenia) = running_life();
+        app_state.is_game_paused = true;
+        app_state.advance_steps = 5;
+        // steps computed 2 at a time don't overshoot
+        app_state.compute_per_render = 2;
+        apply_action(Action::Advance, &mut app_state, &mut lenia);
+        for _ in 0..8 {
+            handle_loop(&UPDATE, &mut app_state, &mut lenia);
+        }
+        assert_eq!(lenia.generation, 5);
+        assert!(app_state.is_game_paused);
+        assert_eq!(app_state.pending_steps, None);
+    }
+}
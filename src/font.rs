@@ -0,0 +1,176 @@
+//! tiny 3x5 bitmap font, to stamp text into the grid as a seed or label the window
+
+use crate::Lenia;
+
+/// width and height of a glyph in cells, glyphs being one column apart
+pub const GLYPH_SIZE: (usize, usize) = (3, 5);
+
+/// the 5 rows of `character` (uppercased), top to bottom, the leftmost column being the highest
+/// of 3 bits; `None` when it isn't in the font
+fn glyph(character: char) -> Option<[u8; 5]> {
+    Some(match character.to_ascii_uppercase() {
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b110, 0b001, 0b010, 0b100, 0b111],
+        '3' => [0b110, 0b001, 0b010, 0b001, 0b110],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b110, 0b001, 0b110],
+        '6' => [0b011, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b010, 0b010, 0b010],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b110],
+        ' ' => [0; 5],
+        '!' => [0b010, 0b010, 0b010, 0b000, 0b010],
+        '?' => [0b110, 0b001, 0b010, 0b000, 0b010],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        '+' => [0b000, 0b010, 0b111, 0b010, 0b000],
+        '=' => [0b000, 0b111, 0b000, 0b111, 0b000],
+        ',' => [0b000, 0b000, 0b000, 0b010, 0b100],
+        ':' => [0b000, 0b010, 0b000, 0b010, 0b000],
+        ';' => [0b000, 0b010, 0b000, 0b010, 0b100],
+        '\'' => [0b010, 0b010, 0b000, 0b000, 0b000],
+        '/' => [0b001, 0b001, 0b010, 0b100, 0b100],
+        '(' => [0b001, 0b010, 0b010, 0b010, 0b001],
+        ')' => [0b100, 0b010, 0b010, 0b010, 0b100],
+        '[' => [0b011, 0b010, 0b010, 0b010, 0b011],
+        ']' => [0b110, 0b010, 0b010, 0b010, 0b110],
+        '{' => [0b011, 0b010, 0b100, 0b010, 0b011],
+        '}' => [0b110, 0b010, 0b001, 0b010, 0b110],
+        '<' => [0b001, 0b010, 0b100, 0b010, 0b001],
+        '>' => [0b100, 0b010, 0b001, 0b010, 0b100],
+        _ => return None,
+    })
+}
+
+/// (width, height) in cells of `text` stamped by [`Lenia::stamp_text`]
+pub fn text_size(text: &str) -> (usize, usize) {
+    let lines: Vec<_> = text.lines().collect();
+    let width = lines
+        .iter()
+        .map(|line| line.chars().filter(|&c| glyph(c).is_some()).count())
+        .max()
+        .unwrap_or(0);
+    let (glyph_w, glyph_h) = GLYPH_SIZE;
+    (
+        (width * (glyph_w + 1)).saturating_sub(1),
+        (lines.len() * (glyph_h + 1)).saturating_sub(1),
+    )
+}
+
+/// (raw, col) of every lit pixel of `text` from its top-left corner, lines going down;
+/// characters out of the font are skipped
+pub fn text_pixels(text: &str) -> impl Iterator<Item = (usize, usize)> + '_ {
+    let (glyph_w, glyph_h) = GLYPH_SIZE;
+    text.lines()
+        .enumerate()
+        .flat_map(move |(line_index, line)| {
+            let top = line_index * (glyph_h + 1);
+            line.chars()
+                .filter_map(glyph)
+                .enumerate()
+                .flat_map(move |(glyph_index, rows)| {
+                    let left = glyph_index * (glyph_w + 1);
+                    rows.into_iter()
+                        .enumerate()
+                        .flat_map(move |(glyph_raw, bits)| {
+                            (0..glyph_w)
+                                .filter(move |glyph_col| {
+                                    bits & (1 << (glyph_w - 1 - glyph_col)) != 0
+                                })
+                                .map(move |glyph_col| (top + glyph_raw, left + glyph_col))
+                        })
+                })
+        })
+}
+
+impl Lenia {
+    /// bring the cells under the glyphs of `text` to life, its top-left corner at (raw, col) and
+    /// lines going down; characters out of the font are skipped, and glyphs out of the grid
+    /// clipped
+    pub fn stamp_text(&mut self, text: &str, (raw, col): (usize, usize)) {
+        let (w, h) = (self.cells[0].len(), self.cells.len());
+        for (text_raw, text_col) in text_pixels(text) {
+            let (cell_raw, cell_col) = (raw + text_raw, col + text_col);
+            if cell_raw < h && cell_col < w {
+                self.cells[cell_raw][cell_col] = 1.0;
+                self.activate_around(cell_raw, cell_col);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Mode;
+
+    fn stamped(text: &str, origin: (usize, usize)) -> Vec<String> {
+        let mut lenia = Lenia::builder()
+            .dimensions(10, 8)
+            .mode(Mode::GameOfLife)
+            .seed(1)
+            .build()
+            .unwrap();
+        lenia.clear();
+        lenia.stamp_text(text, origin);
+        lenia
+            .cells
+            .iter()
+            .map(|cells| {
+                let raw = cells
+                    .iter()
+                    .map(|&cell| if cell == 1.0 { '#' } else { '.' });
+                raw.collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn stamping_hi_lights_its_glyphs_at_the_origin() {
+        let hi = [
+            "..........",
+            "..#.#.###.",
+            "..#.#..#..",
+            "..###..#..",
+            "..#.#..#..",
+            "..#.#.###.",
+            "..........",
+            "..........",
+        ];
+        assert_eq!(stamped("HI", (1, 2)), hi);
+        // lowercase is the same, and characters out of the font are skipped
+        assert_eq!(stamped("h~i", (1, 2)), hi);
+        assert_eq!(text_size("HI"), (7, 5));
+
+        // clipped at the edges of the grid
+        let clipped = stamped("HI", (5, 6));
+        assert_eq!(&clipped[5..], ["......#.#.", "......#.#.", "......###."]);
+    }
+}
@@ -0,0 +1,142 @@
+//! Grayscale images as initial fields, the luminance of each pixel becoming a cell value, and
+//! the other way around to export the cells
+
+use crate::{Cell, ImageFit, Kernel, Lenia};
+use ::image::{imageops, imageops::FilterType, GrayImage, Luma};
+use std::{error::Error, path::Path, sync::Arc};
+
+impl Lenia {
+    /// replace the cells by the image at `path`, resampled to the grid, black being 0 and white 1;
+    /// in Game of Life the cells are thresholded at 0.5
+    pub fn load_image(&mut self, path: &Path, fit: ImageFit) -> Result<(), Box<dyn Error>> {
+        let image = ::image::open(path)?.to_luma8();
+        let (w, h) = (self.cells[0].len(), self.cells.len());
+        let (fitted_w, fitted_h, left, top) = match fit {
+            ImageFit::Stretch => (w, h, 0, 0),
+            ImageFit::Letterbox => {
+                let scale = (w as f64 / image.width() as f64).min(h as f64 / image.height() as f64);
+                let fitted_w = ((image.width() as f64 * scale).round() as usize).clamp(1, w);
+                let fitted_h = ((image.height() as f64 * scale).round() as usize).clamp(1, h);
+                (fitted_w, fitted_h, (w - fitted_w) / 2, (h - fitted_h) / 2)
+            }
+        };
+        let fitted = resample(&image, fitted_w as u32, fitted_h as u32);
+
+        let is_binary = !matches!(self.mode, crate::Mode::Lenia);
+        for (raw, cells) in self.cells.iter_mut().enumerate() {
+            for (col, cell) in cells.iter_mut().enumerate() {
+                let luminance = match (col.checked_sub(left), raw.checked_sub(top)) {
+                    (Some(x), Some(y)) if x < fitted_w && y < fitted_h => {
+                        fitted.get_pixel(x as u32, y as u32).0[0] as Cell / 255.0
+                    }
+                    _ => 0.0,
+                };
+                *cell = match is_binary {
+                    true if luminance >= 0.5 => 1.0,
+                    true => 0.0,
+                    false => luminance,
+                };
+            }
+        }
+        self.age = vec![vec![0; w]; h];
+        self.scan_all_cells = true;
+        self.previous_cells = None;
+        Ok(())
+    }
+}
+
+impl Lenia {
+    /// write the cells to `path` as a grayscale image of one pixel per cell, the inverse of
+    /// [`Lenia::load_image`], the format following the extension (PNG)
+    pub fn save_image(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let (w, h) = (self.cells[0].len(), self.cells.len());
+        let image = GrayImage::from_fn(w as u32, h as u32, |x, y| {
+            Luma([(self.cells[y as usize][x as usize].clamp(0.0, 1.0) * 255.0).round() as u8])
+        });
+        image.save(path)?;
+        Ok(())
+    }
+}
+
+impl Lenia {
+    /// use the grayscale image at `path`, square and odd-sized, as the table of the first kernel
+    /// instead of building it from the peaks: each pixel is the weight of the neighbour at that
+    /// offset from the center pixel (ignored, a cell not being its own neighbour), the weights
+    /// being normalized to sum to 1; the kernel radius becomes the half size of the image
+    pub fn load_kernel_image(&mut self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let image = ::image::open(path)?.to_luma8();
+        let (w, h) = image.dimensions();
+        if w != h || w % 2 == 0 {
+            return Err(format!("a kernel image must be square and odd-sized, got {w}x{h}").into());
+        }
+        let radius = (w / 2) as isize;
+        let mut weights: Vec<_> = image
+            .enumerate_pixels()
+            .map(|(x, y, pixel)| {
+                (
+                    y as isize - radius,
+                    x as isize - radius,
+                    pixel.0[0] as f64 / 255.0,
+                )
+            })
+            .filter(|&(draw, dcol, weight)| (draw, dcol) != (0, 0) && weight > 0.0)
+            .collect();
+        let total_weight: f64 = weights.iter().map(|&(_, _, weight)| weight).sum();
+        if total_weight == 0.0 {
+            return Err(
+                "a kernel image needs at least one non-black pixel besides the center".into(),
+            );
+        }
+        for (_, _, weight) in weights.iter_mut() {
+            *weight /= total_weight;
+        }
+
+        let radius = radius as usize;
+        self.kernel_radius = (radius, radius);
+        let spec = &mut self.kernels[0];
+        spec.relative_radius = 1.0;
+        spec.table = Kernel {
+            radius: (radius, radius),
+            peaks: spec.peaks.clone(),
+            weights: Arc::new(weights),
+            is_custom: true,
+            ..Kernel::default()
+        };
+        Ok(())
+    }
+}
+
+/// nearest pixel when the size is kept (exact values), a linear filter otherwise
+fn resample(image: &GrayImage, w: u32, h: u32) -> GrayImage {
+    if image.dimensions() == (w, h) {
+        image.clone()
+    } else {
+        imageops::resize(image, w, h, FilterType::Triangle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Mode;
+
+    #[test]
+    fn image_pixels_become_their_luminance() {
+        let path = std::env::temp_dir().join(format!("lenia-image-{}.png", std::process::id()));
+        let image = GrayImage::from_raw(2, 2, vec![0, 51, 204, 255]).unwrap();
+        image.save(&path).unwrap();
+        let build = |mode| {
+            let lenia = Lenia::builder().dimensions(2, 2).mode(mode).seed(1);
+            lenia.build().unwrap()
+        };
+        let mut lenia = build(Mode::Lenia);
+        let loaded = lenia.load_image(&path, ImageFit::Stretch);
+        let mut life = build(Mode::GameOfLife);
+        let thresholded = life.load_image(&path, ImageFit::Stretch);
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(loaded.is_ok() && thresholded.is_ok());
+        assert_eq!(lenia.cells, vec![vec![0.0, 0.2], vec![0.8, 1.0]]);
+        assert_eq!(life.cells, vec![vec![0.0, 0.0], vec![1.0, 1.0]]);
+    }
+}
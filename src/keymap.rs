@@ -0,0 +1,358 @@
+//! Keyboard bindings, decoupling the typed characters from what they do
+
+use ahash::AHashMap;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Reset,
+    ReseedEmpty,
+    Clear,
+    ClearInsideBorder,
+    DrawBorder,
+    LowerSpawnDensity,
+    RaiseSpawnDensity,
+    Invert,
+    InjectNoise,
+    LowerNoiseAmplitude,
+    RaiseNoiseAmplitude,
+    Start,
+    Halt,
+    Advance,
+    UndoStep,
+    ToggleMode,
+    ToggleBenchmark,
+    ToggleDraw,
+    ToggleBrushShape,
+    CycleDrawBlend,
+    ShrinkBrush,
+    GrowBrush,
+    LowerPaintIntensity,
+    RaisePaintIntensity,
+    SaveNpy,
+    SaveRle,
+    PlotKernelAndGrowth,
+    SavePreset,
+    NextPreset,
+    RandomizeParams,
+    ShrinkKernelRadius,
+    GrowKernelRadius,
+    LowerKernelAlpha,
+    RaiseKernelAlpha,
+    CycleTunedParameter,
+    ToggleQuantizedRender,
+    LowerRenderThreshold,
+    RaiseRenderThreshold,
+    ToggleAgeRender,
+    ToggleSmoothRender,
+    ToggleGridLines,
+    ToggleRuler,
+    ToggleTheme,
+    ToggleActiveCells,
+    ToggleVonNeumann,
+    ToggleKernelView,
+    CaptureReference,
+    ToggleDiffView,
+    ToggleHotspot,
+    TogglePeriodDetection,
+    ToggleMassConservation,
+    ToggleWrapX,
+    ToggleWrapY,
+    CycleTopology,
+    RenderLessOften,
+    RenderMoreOften,
+    MoreStepsPerFrame,
+    FewerStepsPerFrame,
+    ToggleHelp,
+}
+
+impl Action {
+    /// every action along with its name, as used by `--bind`
+    pub const ALL: [(Action, &'static str); 59] = [
+        (Action::Reset, "reset"),
+        (Action::ReseedEmpty, "reseed-empty"),
+        (Action::Clear, "clear"),
+        (Action::ClearInsideBorder, "clear-inside-border"),
+        (Action::DrawBorder, "draw-border"),
+        (Action::LowerSpawnDensity, "lower-spawn-density"),
+        (Action::RaiseSpawnDensity, "raise-spawn-density"),
+        (Action::Invert, "invert"),
+        (Action::InjectNoise, "inject-noise"),
+        (Action::LowerNoiseAmplitude, "lower-noise-amplitude"),
+        (Action::RaiseNoiseAmplitude, "raise-noise-amplitude"),
+        (Action::Start, "start"),
+        (Action::Halt, "halt"),
+        (Action::Advance, "advance"),
+        (Action::UndoStep, "undo-step"),
+        (Action::ToggleMode, "toggle-mode"),
+        (Action::ToggleBenchmark, "toggle-benchmark"),
+        (Action::ToggleDraw, "toggle-draw"),
+        (Action::ToggleBrushShape, "toggle-brush-shape"),
+        (Action::CycleDrawBlend, "cycle-draw-blend"),
+        (Action::ShrinkBrush, "shrink-brush"),
+        (Action::GrowBrush, "grow-brush"),
+        (Action::LowerPaintIntensity, "lower-paint-intensity"),
+        (Action::RaisePaintIntensity, "raise-paint-intensity"),
+        (Action::SaveNpy, "save-npy"),
+        (Action::SaveRle, "save-rle"),
+        (Action::PlotKernelAndGrowth, "plot-kernel-and-growth"),
+        (Action::SavePreset, "save-preset"),
+        (Action::NextPreset, "next-preset"),
+        (Action::RandomizeParams, "randomize-params"),
+        (Action::ShrinkKernelRadius, "shrink-kernel-radius"),
+        (Action::GrowKernelRadius, "grow-kernel-radius"),
+        (Action::LowerKernelAlpha, "lower-kernel-alpha"),
+        (Action::RaiseKernelAlpha, "raise-kernel-alpha"),
+        (Action::CycleTunedParameter, "cycle-tuned-parameter"),
+        (Action::ToggleQuantizedRender, "toggle-threshold-render"),
+        (Action::LowerRenderThreshold, "lower-render-threshold"),
+        (Action::RaiseRenderThreshold, "raise-render-threshold"),
+        (Action::ToggleAgeRender, "toggle-age-render"),
+        (Action::ToggleSmoothRender, "toggle-smooth-render"),
+        (Action::ToggleGridLines, "toggle-grid-lines"),
+        (Action::ToggleRuler, "toggle-ruler"),
+        (Action::ToggleTheme, "toggle-theme"),
+        (Action::ToggleActiveCells, "toggle-active-cells"),
+        (Action::ToggleVonNeumann, "toggle-von-neumann"),
+        (Action::ToggleKernelView, "toggle-kernel-view"),
+        (Action::CaptureReference, "capture-reference"),
+        (Action::ToggleDiffView, "toggle-diff-view"),
+        (Action::ToggleHotspot, "toggle-hotspot"),
+        (Action::TogglePeriodDetection, "toggle-period-detection"),
+        (Action::ToggleMassConservation, "toggle-mass-conservation"),
+        (Action::ToggleWrapX, "toggle-wrap-x"),
+        (Action::ToggleWrapY, "toggle-wrap-y"),
+        (Action::CycleTopology, "cycle-topology"),
+        (Action::RenderLessOften, "render-less-often"),
+        (Action::RenderMoreOften, "render-more-often"),
+        (Action::MoreStepsPerFrame, "more-steps-per-frame"),
+        (Action::FewerStepsPerFrame, "fewer-steps-per-frame"),
+        (Action::ToggleHelp, "toggle-help"),
+    ];
+}
+
+impl FromStr for Action {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .find(|(_, action_name)| *action_name == name)
+            .map(|&(action, _)| action)
+            .ok_or_else(|| format!("unknown action \"{name}\""))
+    }
+}
+
+/// `<character>=<action name>`, e.g. `x=clear`
+pub struct KeyBinding(pub char, pub Action);
+
+impl FromStr for KeyBinding {
+    type Err = String;
+
+    fn from_str(binding: &str) -> Result<Self, Self::Err> {
+        let mut chars = binding.chars();
+        match (chars.next(), chars.next()) {
+            (Some(character), Some('=')) => Ok(Self(character, chars.as_str().parse()?)),
+            _ => Err("expected <character>=<action>, e.g. x=clear".to_string()),
+        }
+    }
+}
+
+pub struct Keymap {
+    bindings: AHashMap<char, Action>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self {
+            bindings: AHashMap::from_iter([
+                ('r', Action::Reset),
+                ('z', Action::ReseedEmpty),
+                ('c', Action::Clear),
+                ('j', Action::ClearInsideBorder),
+                ('q', Action::DrawBorder),
+                (';', Action::LowerSpawnDensity),
+                ('\'', Action::RaiseSpawnDensity),
+                ('i', Action::Invert),
+                ('x', Action::InjectNoise),
+                ('{', Action::LowerNoiseAmplitude),
+                ('}', Action::RaiseNoiseAmplitude),
+                ('s', Action::Start),
+                ('h', Action::Halt),
+                ('p', Action::Advance),
+                ('u', Action::UndoStep),
+                ('y', Action::ToggleMode),
+                ('f', Action::ToggleBenchmark),
+                ('d', Action::ToggleDraw),
+                ('b', Action::ToggleBrushShape),
+                ('e', Action::CycleDrawBlend),
+                ('-', Action::ShrinkBrush),
+                ('+', Action::GrowBrush),
+                ('=', Action::GrowBrush),
+                (',', Action::LowerPaintIntensity),
+                ('.', Action::RaisePaintIntensity),
+                ('n', Action::SaveNpy),
+                ('N', Action::SaveRle),
+                ('k', Action::PlotKernelAndGrowth),
+                ('w', Action::SavePreset),
+                ('l', Action::NextPreset),
+                ('E', Action::RandomizeParams),
+                ('7', Action::ShrinkKernelRadius),
+                ('8', Action::GrowKernelRadius),
+                ('9', Action::LowerKernelAlpha),
+                ('0', Action::RaiseKernelAlpha),
+                ('P', Action::CycleTunedParameter),
+                ('t', Action::ToggleQuantizedRender),
+                ('[', Action::LowerRenderThreshold),
+                (']', Action::RaiseRenderThreshold),
+                ('a', Action::ToggleAgeRender),
+                ('o', Action::ToggleSmoothRender),
+                ('g', Action::ToggleGridLines),
+                ('R', Action::ToggleRuler),
+                ('T', Action::ToggleTheme),
+                ('v', Action::ToggleActiveCells),
+                ('V', Action::ToggleVonNeumann),
+                ('K', Action::ToggleKernelView),
+                ('S', Action::CaptureReference),
+                ('D', Action::ToggleDiffView),
+                ('H', Action::ToggleHotspot),
+                ('O', Action::TogglePeriodDetection),
+                ('m', Action::ToggleMassConservation),
+                ('X', Action::ToggleWrapX),
+                ('Y', Action::ToggleWrapY),
+                ('B', Action::CycleTopology),
+                ('>', Action::RenderLessOften),
+                ('<', Action::RenderMoreOften),
+                (')', Action::MoreStepsPerFrame),
+                ('(', Action::FewerStepsPerFrame),
+                ('?', Action::ToggleHelp),
+            ]),
+        }
+    }
+}
+
+impl Keymap {
+    pub fn action(&self, character: char) -> Option<Action> {
+        self.bindings.get(&character).copied()
+    }
+
+    /// bind `character` to `action`, replacing whatever it was bound to
+    pub fn bind(&mut self, character: char, action: Action) {
+        self.bindings.insert(character, action);
+    }
+
+    /// `<keys> <action name>` for every bound action in the order of [`Action::ALL`], the keys
+    /// padded so that the names line up; an uppercase key is written `shift-<key>`, which reads
+    /// the same in any case
+    pub fn help_lines(&self) -> Vec<String> {
+        let key_lists: Vec<_> = Action::ALL
+            .iter()
+            .filter_map(|&(action, name)| {
+                let mut keys: Vec<_> = self
+                    .bindings
+                    .iter()
+                    .filter(|&(_, &bound)| bound == action)
+                    .map(|(&character, _)| character)
+                    .collect();
+                keys.sort_unstable();
+                let keys = keys
+                    .into_iter()
+                    .map(|character| match character {
+                        ' ' => "space".to_string(),
+                        _ if character.is_uppercase() => {
+                            format!("shift-{}", character.to_lowercase())
+                        }
+                        _ => character.to_string(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                (!keys.is_empty()).then_some((keys, name))
+            })
+            .collect();
+        let keys_width = key_lists
+            .iter()
+            .map(|(keys, _)| keys.chars().count())
+            .max()
+            .unwrap_or(0);
+        key_lists
+            .into_iter()
+            .map(|(keys, name)| format!("{keys:<keys_width$} {name}"))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn action_names_parse_back_to_their_action() {
+        for (index, &(action, name)) in Action::ALL.iter().enumerate() {
+            assert_eq!(name.parse(), Ok(action));
+            assert!(
+                Action::ALL[..index]
+                    .iter()
+                    .all(|&(other, other_name)| other != action && other_name != name),
+                "{name}"
+            );
+        }
+        assert!("Reset".parse::<Action>().is_err());
+        assert!("".parse::<Action>().is_err());
+    }
+
+    #[test]
+    fn every_action_has_a_default_key() {
+        let keymap = Keymap::default();
+        for (action, name) in Action::ALL {
+            assert!(
+                keymap.bindings.values().any(|&bound| bound == action),
+                "{name}"
+            );
+        }
+        assert_eq!(keymap.action('r'), Some(Action::Reset));
+        assert_eq!(keymap.action('n'), Some(Action::SaveNpy));
+        assert_eq!(keymap.action('~'), None);
+    }
+
+    #[test]
+    fn key_bindings_parse_from_bind_options() {
+        let parse = |binding: &str| binding.parse::<KeyBinding>().map(|KeyBinding(c, a)| (c, a));
+        assert_eq!(parse("x=clear"), Ok(('x', Action::Clear)));
+        assert_eq!(parse("==reset"), Ok(('=', Action::Reset)));
+        assert_eq!(parse("é=halt"), Ok(('é', Action::Halt)));
+        for invalid in ["", "x", "x=", "xclear", "xy=clear", "x=nothing"] {
+            assert!(parse(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn bind_replaces_the_action_of_a_key() {
+        let mut keymap = Keymap::default();
+        keymap.bind('r', Action::Clear);
+        keymap.bind('~', Action::Reset);
+        assert_eq!(keymap.action('r'), Some(Action::Clear));
+        assert_eq!(keymap.action('~'), Some(Action::Reset));
+        assert_eq!(keymap.action('c'), Some(Action::Clear));
+    }
+
+    #[test]
+    fn help_lines_list_the_bound_keys_of_every_action() {
+        let mut keymap = Keymap::default();
+        let lines = keymap.help_lines();
+        assert_eq!(lines.len(), Action::ALL.len());
+        assert_eq!(lines[0], "r       reset");
+        assert!(lines.contains(&"+ =     grow-brush".to_string()));
+        assert!(lines.contains(&"shift-r toggle-ruler".to_string()));
+        // the names line up
+        let name_column = |line: &String| line.rfind(' ').unwrap();
+        assert!(lines.iter().all(|line| name_column(line) == 7), "{lines:?}");
+
+        // rebound: the ruler loses its only key, reset gains one
+        keymap.bind('R', Action::Reset);
+        keymap.bind(' ', Action::Reset);
+        let lines = keymap.help_lines();
+        assert_eq!(lines.len(), Action::ALL.len() - 1);
+        assert_eq!(lines[0], "space shift-r r reset");
+        assert!(!lines.iter().any(|line| line.ends_with("toggle-ruler")));
+    }
+}
@@ -0,0 +1,3815 @@
+//! Lenia and Game of Life simulation core, free of any windowing or rendering
+// casts between `Cell` and f32 or f64 are no-ops with one of the two cell types
+#![allow(clippy::unnecessary_cast)]
+
+use ahash::{AHashMap, AHashSet};
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Ordering,
+    collections::VecDeque,
+    hash::{BuildHasher, Hash, Hasher},
+    io::Write,
+    path::Path,
+    str::FromStr,
+    sync::Arc,
+};
+
+mod builder;
+mod font;
+#[cfg(feature = "image")]
+mod image;
+#[cfg(feature = "plot")]
+mod plot;
+mod preset;
+mod rle;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+pub use builder::LeniaBuilder;
+pub use font::{text_pixels, text_size, GLYPH_SIZE};
+pub use preset::Preset;
+pub use rle::{parse_rle, RlePattern};
+
+pub enum Mode {
+    Lenia,
+    GameOfLife,
+    /// Game of Life with dying states, see [`GenerationsRule`]
+    Generations(GenerationsRule),
+    /// any other rule, plugged in from outside the crate
+    Custom(Box<dyn Automaton>),
+}
+
+/// value of a cell, `f64` with the `f64-cells` feature: twice the memory, but steps keep the
+/// precision of the f64 convolution instead of rounding every cell to `f32`
+#[cfg(not(feature = "f64-cells"))]
+pub type Cell = f32;
+#[cfg(feature = "f64-cells")]
+pub type Cell = f64;
+
+pub type Grid = Vec<Vec<Cell>>;
+
+/// smallest width and height the simulation runs on, the Lenia wrap-around needs 2 cells
+pub const MIN_GRID_SIZE: usize = 2;
+
+/// `delta_t` of a new simulation, and of Lenia when switched to, see [`Lenia::set_mode`]
+pub const DEFAULT_DELTA_T: f64 = 1.0;
+
+/// kernel radius of a new simulation, and of Lenia when switched to
+pub const DEFAULT_KERNEL_RADIUS: usize = 13;
+
+/// a cellular automaton rule, for modes beyond the built-in Lenia and Game of Life
+pub trait Automaton {
+    /// compute the frame after `prev` into `next`, which starts out as a copy of `prev`
+    fn step(&mut self, prev: &Grid, next: &mut Grid);
+
+    /// fill `grid` with a random state, used to seed spawn areas
+    fn init(&self, grid: &mut Grid, rng: &mut fastrand::Rng) {
+        for cell in grid.iter_mut().flatten() {
+            *cell = rng.f32() as Cell;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BrushShape {
+    Square,
+    Disc,
+}
+
+impl BrushShape {
+    /// cell offsets (raw, col) covered by a brush of this shape, relative to its center
+    pub fn offsets(self, radius: usize) -> impl Iterator<Item = (isize, isize)> {
+        let radius = radius as isize;
+        (-radius..=radius)
+            .flat_map(move |draw| (-radius..=radius).map(move |dcol| (draw, dcol)))
+            .filter(move |&(draw, dcol)| match self {
+                BrushShape::Square => true,
+                BrushShape::Disc => draw * draw + dcol * dcol <= radius * radius,
+            })
+    }
+}
+
+/// how painted values combine with the cells under the brush
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DrawBlend {
+    #[default]
+    Overwrite,
+    /// accumulate with repeated strokes, saturating at 1
+    Add,
+    /// remove with repeated strokes, saturating at 0
+    Subtract,
+}
+
+/// which edges of the grid wrap around to the opposite one: both pairs for a torus (the default),
+/// one for a cylinder, none for a plane, where the neighbours beyond an edge are dead (0); a
+/// joined pair can also be flipped, see [`Topology`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Boundary {
+    /// the left and right edges are joined
+    pub wrap_x: bool,
+    /// the top and bottom edges are joined
+    pub wrap_y: bool,
+    /// crossing the joined left and right edges mirrors the raw, bottom coming back at the top
+    pub flip_x: bool,
+    /// crossing the joined top and bottom edges mirrors the col, right coming back at the left
+    pub flip_y: bool,
+}
+
+impl Default for Boundary {
+    fn default() -> Self {
+        Topology::Torus.boundary()
+    }
+}
+
+impl Boundary {
+    /// (raw, col) of the cell at offset (draw, dcol) from (raw, col) on a (w, h) grid, `None`
+    /// beyond an edge that doesn't wrap
+    pub fn neighbour(
+        self,
+        (w, h): (usize, usize),
+        (raw, col): (usize, usize),
+        (draw, dcol): (isize, isize),
+    ) -> Option<(usize, usize)> {
+        // the index, and whether it lands across the seam (crossing it twice comes back)
+        let shift = |index: usize, offset: isize, size: usize, wraps: bool| {
+            let index = index as isize + offset;
+            if wraps {
+                let size = size as isize;
+                Some((
+                    index.rem_euclid(size) as usize,
+                    index.div_euclid(size) % 2 != 0,
+                ))
+            } else {
+                (0..size as isize)
+                    .contains(&index)
+                    .then_some((index as usize, false))
+            }
+        };
+        let (raw, crosses_y) = shift(raw, draw, h, self.wrap_y)?;
+        let (col, crosses_x) = shift(col, dcol, w, self.wrap_x)?;
+        Some((
+            if self.flip_x && crosses_x {
+                h - 1 - raw
+            } else {
+                raw
+            },
+            if self.flip_y && crosses_y {
+                w - 1 - col
+            } else {
+                col
+            },
+        ))
+    }
+
+    /// the surface the joined edges make, a cylinder or Möbius strip either way
+    pub fn topology(self) -> Topology {
+        let (flip_x, flip_y) = (self.wrap_x && self.flip_x, self.wrap_y && self.flip_y);
+        match (self.wrap_x, self.wrap_y) {
+            (false, false) => Topology::Plane,
+            (true, true) => match (flip_x, flip_y) {
+                (false, false) => Topology::Torus,
+                (true, true) => Topology::ProjectivePlane,
+                _ => Topology::KleinBottle,
+            },
+            _ if flip_x || flip_y => Topology::MobiusStrip,
+            _ => Topology::Cylinder,
+        }
+    }
+}
+
+/// the surfaces the edges of the grid can be joined into, see [`Topology::boundary`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Topology {
+    /// no edge joined
+    Plane,
+    /// the left and right edges joined
+    Cylinder,
+    /// the left and right edges joined upside down
+    MobiusStrip,
+    /// the left and right edges joined, and the top and bottom ones
+    #[default]
+    Torus,
+    /// a torus whose left and right edges are joined upside down
+    KleinBottle,
+    /// a torus whose both pairs of edges are joined upside down
+    ProjectivePlane,
+}
+
+impl Topology {
+    pub fn boundary(self) -> Boundary {
+        let (wrap_x, wrap_y, flip_x, flip_y) = match self {
+            Topology::Plane => (false, false, false, false),
+            Topology::Cylinder => (true, false, false, false),
+            Topology::MobiusStrip => (true, false, true, false),
+            Topology::Torus => (true, true, false, false),
+            Topology::KleinBottle => (true, true, true, false),
+            Topology::ProjectivePlane => (true, true, true, true),
+        };
+        Boundary {
+            wrap_x,
+            wrap_y,
+            flip_x,
+            flip_y,
+        }
+    }
+
+    /// the next one in declaration order, back to the plane after the projective plane
+    pub fn next(self) -> Self {
+        match self {
+            Topology::Plane => Topology::Cylinder,
+            Topology::Cylinder => Topology::MobiusStrip,
+            Topology::MobiusStrip => Topology::Torus,
+            Topology::Torus => Topology::KleinBottle,
+            Topology::KleinBottle => Topology::ProjectivePlane,
+            Topology::ProjectivePlane => Topology::Plane,
+        }
+    }
+}
+
+impl FromStr for Topology {
+    type Err = String;
+
+    fn from_str(topology: &str) -> Result<Self, Self::Err> {
+        match topology {
+            "plane" => Ok(Self::Plane),
+            "cylinder" => Ok(Self::Cylinder),
+            "mobius-strip" => Ok(Self::MobiusStrip),
+            "torus" => Ok(Self::Torus),
+            "klein-bottle" => Ok(Self::KleinBottle),
+            "projective-plane" => Ok(Self::ProjectivePlane),
+            _ => Err(
+                "expected \"plane\", \"cylinder\", \"mobius-strip\", \"torus\", \
+                 \"klein-bottle\" or \"projective-plane\""
+                    .to_string(),
+            ),
+        }
+    }
+}
+
+impl std::fmt::Display for Topology {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Topology::Plane => "plane",
+            Topology::Cylinder => "cylinder",
+            Topology::MobiusStrip => "Möbius strip",
+            Topology::Torus => "torus",
+            Topology::KleinBottle => "Klein bottle",
+            Topology::ProjectivePlane => "projective plane",
+        })
+    }
+}
+
+/// the cells a Game of Life (or Generations) cell counts as its neighbours
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Neighbourhood {
+    /// the 4 orthogonal cells
+    VonNeumann,
+    /// the 8 surrounding cells, classic Life
+    #[default]
+    Moore,
+    /// the cells up to this many cells away in both directions, (2R + 1)² - 1 of them, e.g. for
+    /// "Larger than Life" rules, see [`MAX_NEIGHBOURHOOD_RANGE`]
+    MooreRange(usize),
+}
+
+/// neighbour counts are stored as `u8`, (2 * 7 + 1)² - 1 = 224 fitting in it
+pub const MAX_NEIGHBOURHOOD_RANGE: usize = 7;
+
+impl Neighbourhood {
+    /// (raw offset, col offset) of every neighbour, raw by raw
+    pub fn offsets(self) -> impl Iterator<Item = (isize, isize)> {
+        let range = match self {
+            Neighbourhood::VonNeumann | Neighbourhood::Moore => 1,
+            Neighbourhood::MooreRange(range) => range as isize,
+        };
+        (-range..=range)
+            .flat_map(move |draw| (-range..=range).map(move |dcol| (draw, dcol)))
+            .filter(move |&(draw, dcol)| {
+                (draw, dcol) != (0, 0)
+                    && (self != Neighbourhood::VonNeumann || draw == 0 || dcol == 0)
+            })
+    }
+}
+
+impl FromStr for Neighbourhood {
+    type Err = String;
+
+    fn from_str(neighbourhood: &str) -> Result<Self, Self::Err> {
+        match neighbourhood {
+            "moore" => Ok(Self::Moore),
+            "von-neumann" => Ok(Self::VonNeumann),
+            range => match range.strip_prefix(['R', 'r']).map(str::parse) {
+                Some(Ok(range @ 1..=MAX_NEIGHBOURHOOD_RANGE)) => Ok(Self::MooreRange(range)),
+                _ => Err(format!(
+                    "expected \"moore\", \"von-neumann\" or a range from R1 to \
+                     R{MAX_NEIGHBOURHOOD_RANGE}"
+                )),
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for Neighbourhood {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Neighbourhood::VonNeumann => f.write_str("von Neumann (4 orthogonal cells)"),
+            Neighbourhood::Moore => f.write_str("Moore (8 surrounding cells)"),
+            Neighbourhood::MooreRange(range) => write!(f, "Moore range {range}"),
+        }
+    }
+}
+
+/// how [`Lenia::seed_symmetric`] mirrors a randomized part of the grid across its center
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Symmetry {
+    /// the left half mirrored onto the right one
+    MirrorX,
+    /// the top half mirrored onto the bottom one
+    MirrorY,
+    /// the top half turned around the center onto the bottom one
+    Point,
+    /// the top-left quadrant mirrored onto the three others
+    Quadrants,
+}
+
+impl Symmetry {
+    /// the cell of the randomized part that (raw, col) copies, itself when it's in that part
+    fn source(self, (raw, col): (usize, usize), (w, h): (usize, usize)) -> (usize, usize) {
+        let (mirrored_raw, mirrored_col) = (h - 1 - raw, w - 1 - col);
+        match self {
+            Symmetry::MirrorX => (raw, col.min(mirrored_col)),
+            Symmetry::MirrorY => (raw.min(mirrored_raw), col),
+            Symmetry::Point => match raw.cmp(&mirrored_raw) {
+                Ordering::Less => (raw, col),
+                Ordering::Greater => (mirrored_raw, mirrored_col),
+                // the middle raw of an odd height turns onto itself
+                Ordering::Equal => (raw, col.min(mirrored_col)),
+            },
+            Symmetry::Quadrants => (raw.min(mirrored_raw), col.min(mirrored_col)),
+        }
+    }
+
+    /// (raw, col) and the cells it's mirrored onto, which copy the same cell of the randomized
+    /// part, e.g. to paint symmetrically
+    pub fn images(self, (raw, col): (usize, usize), (w, h): (usize, usize)) -> Vec<(usize, usize)> {
+        let (mirrored_raw, mirrored_col) = (h - 1 - raw, w - 1 - col);
+        let mut images = match self {
+            Symmetry::MirrorX => vec![(raw, col), (raw, mirrored_col)],
+            Symmetry::MirrorY => vec![(raw, col), (mirrored_raw, col)],
+            Symmetry::Point => vec![(raw, col), (mirrored_raw, mirrored_col)],
+            Symmetry::Quadrants => vec![
+                (raw, col),
+                (raw, mirrored_col),
+                (mirrored_raw, col),
+                (mirrored_raw, mirrored_col),
+            ],
+        };
+        // the middle raw or col is its own image
+        images.sort_unstable();
+        images.dedup();
+        images
+    }
+}
+
+impl FromStr for Symmetry {
+    type Err = String;
+
+    fn from_str(symmetry: &str) -> Result<Self, Self::Err> {
+        match symmetry {
+            "mirror-x" => Ok(Self::MirrorX),
+            "mirror-y" => Ok(Self::MirrorY),
+            "point" => Ok(Self::Point),
+            "quadrants" => Ok(Self::Quadrants),
+            _ => Err("expected \"mirror-x\", \"mirror-y\", \"point\" or \"quadrants\"".to_string()),
+        }
+    }
+}
+
+/// the order the cells of a Game of Life step are updated in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UpdateOrder {
+    /// every cell from the cells of the previous step, classic Life
+    #[default]
+    Synchronous,
+    /// the cells whose raw + col is even first, then the odd ones from the grid the even ones
+    /// left: the odd cells count their even neighbours' new states, so the gliders and
+    /// oscillators of a rule rarely survive this order, which has its own patterns
+    Checkerboard,
+}
+
+impl FromStr for UpdateOrder {
+    type Err = String;
+
+    fn from_str(order: &str) -> Result<Self, Self::Err> {
+        match order {
+            "synchronous" => Ok(Self::Synchronous),
+            "checkerboard" => Ok(Self::Checkerboard),
+            _ => Err("expected \"synchronous\" or \"checkerboard\"".to_string()),
+        }
+    }
+}
+
+/// where the existing content stays when the grid is resized
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ResizeAnchor {
+    /// new cells are added (or removed) on the right and bottom edges
+    #[default]
+    TopLeft,
+    /// new cells are added (or removed) evenly on every edge, keeping the content centered
+    Center,
+}
+
+impl FromStr for ResizeAnchor {
+    type Err = String;
+
+    fn from_str(anchor: &str) -> Result<Self, Self::Err> {
+        match anchor {
+            "top-left" => Ok(Self::TopLeft),
+            "center" => Ok(Self::Center),
+            _ => Err("expected \"top-left\" or \"center\"".to_string()),
+        }
+    }
+}
+
+/// how a Lenia step maps `cell + delta_t * growth` back into [0, 1]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Saturation {
+    /// values beyond the bounds stick to them
+    #[default]
+    Clamp,
+    /// soft saturation: `tanh` of the value, negative values being 0, so that cells approach 1
+    /// without ever reaching it (values within [0, 1] are lowered too)
+    Tanh,
+    /// the fractional part of the value, e.g. 1.5 becomes 0.5 and -0.25 becomes 0.75
+    Wrap,
+}
+
+impl Saturation {
+    pub fn apply(self, value: f64) -> f64 {
+        match self {
+            Saturation::Clamp => value.clamp(0.0, 1.0),
+            Saturation::Tanh => value.max(0.0).tanh(),
+            Saturation::Wrap => value.rem_euclid(1.0),
+        }
+    }
+}
+
+impl FromStr for Saturation {
+    type Err = String;
+
+    fn from_str(saturation: &str) -> Result<Self, Self::Err> {
+        match saturation {
+            "clamp" => Ok(Self::Clamp),
+            "tanh" => Ok(Self::Tanh),
+            "wrap" => Ok(Self::Wrap),
+            _ => Err("expected \"clamp\", \"tanh\" or \"wrap\"".to_string()),
+        }
+    }
+}
+
+/// what the growth of a Lenia cell is a function of
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Potential {
+    /// the convolution of the neighbourhood with the kernel, classic Lenia
+    #[default]
+    Kernel,
+    /// SmoothLife-like: the average of the disc of `inner_fraction` of the kernel radius around
+    /// the cell (itself included), divided by the average of the ring between that disc and the
+    /// kernel radius, so that a uniform field is at 1 everywhere; the kernel peaks are ignored
+    InnerOuterRatio { inner_fraction: f64 },
+}
+
+/// how an image of another aspect ratio than the grid is fitted to it, see `Lenia::load_image`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ImageFit {
+    /// the image covers the whole grid, distorted
+    #[default]
+    Stretch,
+    /// the image keeps its aspect ratio, centered between dead cells
+    Letterbox,
+}
+
+impl FromStr for ImageFit {
+    type Err = String;
+
+    fn from_str(fit: &str) -> Result<Self, Self::Err> {
+        match fit {
+            "stretch" => Ok(Self::Stretch),
+            "letterbox" => Ok(Self::Letterbox),
+            _ => Err("expected \"stretch\" or \"letterbox\"".to_string()),
+        }
+    }
+}
+
+/// Life-like rule in B/S notation, e.g. "B3/S23" (Conway) or "B36/S23" (HighLife); counts above
+/// 8, for the larger neighbourhoods, are listed with commas and ranges, as in "B34..45/S33..57"
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GolRule {
+    /// neighbour counts that bring a dead cell to life
+    pub birth: Vec<u8>,
+    /// neighbour counts that keep a live cell alive
+    pub survive: Vec<u8>,
+}
+
+impl Default for GolRule {
+    fn default() -> Self {
+        Self {
+            birth: vec![3],
+            survive: vec![2, 3],
+        }
+    }
+}
+
+/// B/S notation, the way [`GolRule::from_str`] parses it
+impl std::fmt::Display for GolRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let counts = |counts: &[u8]| {
+            if counts.iter().all(|&count| count <= 8) {
+                return counts.iter().map(u8::to_string).collect::<String>();
+            }
+            // runs of consecutive counts as ranges
+            let mut runs: Vec<(u8, u8)> = vec![];
+            for &count in counts {
+                match runs.last_mut() {
+                    Some((_, last)) if *last + 1 == count => *last = count,
+                    _ => runs.push((count, count)),
+                }
+            }
+            let runs: Vec<_> = runs
+                .into_iter()
+                .map(|(first, last)| match first == last {
+                    true => first.to_string(),
+                    false => format!("{first}..{last}"),
+                })
+                .collect();
+            // a lone count needs a comma to be read as a number rather than digits
+            match runs.as_slice() {
+                [count] if !count.contains("..") => format!("{count},"),
+                _ => runs.join(","),
+            }
+        };
+        write!(f, "B{}/S{}", counts(&self.birth), counts(&self.survive))
+    }
+}
+
+impl FromStr for GolRule {
+    type Err = String;
+
+    fn from_str(rule: &str) -> Result<Self, Self::Err> {
+        fn parse_counts(part: &str, prefix: char) -> Result<Vec<u8>, String> {
+            let mut chars = part.chars();
+            if !chars
+                .next()
+                .is_some_and(|c| c.eq_ignore_ascii_case(&prefix))
+            {
+                return Err(format!("expected '{prefix}' at the start of \"{part}\""));
+            }
+
+            let mut counts = vec![];
+            let list = chars.as_str();
+            if list.contains([',', '.']) {
+                // "34..45,50": numbers and inclusive ranges
+                for item in list
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|item| !item.is_empty())
+                {
+                    let invalid = || format!("invalid neighbour counts \"{item}\" in \"{part}\"");
+                    let (first, last) = item.split_once("..").unwrap_or((item, item));
+                    let first: u8 = first.parse().map_err(|_| invalid())?;
+                    let last: u8 = last.parse().map_err(|_| invalid())?;
+                    counts.extend(first..=last);
+                }
+                counts.sort_unstable();
+                counts.dedup();
+                return Ok(counts);
+            }
+            for c in chars {
+                match c.to_digit(10) {
+                    Some(count @ 0..=8) => {
+                        if !counts.contains(&(count as u8)) {
+                            counts.push(count as u8)
+                        }
+                    }
+                    _ => return Err(format!("invalid neighbour count '{c}' in \"{part}\"")),
+                }
+            }
+            counts.sort_unstable();
+            Ok(counts)
+        }
+
+        let (birth, survive) = rule
+            .trim()
+            .split_once('/')
+            .ok_or_else(|| format!("rule \"{rule}\" is not in B/S notation (e.g. B3/S23)"))?;
+        Ok(Self {
+            birth: parse_counts(birth, 'B')?,
+            survive: parse_counts(survive, 'S')?,
+        })
+    }
+}
+
+/// Generations rule in B/S/C notation, e.g. "B2/S/3" (Brian's Brain): Life-like birth and
+/// survival, but a live cell that doesn't survive goes through C - 2 dying states before being
+/// dead, dying cells neither counting as neighbours nor being born again
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenerationsRule {
+    pub life: GolRule,
+    /// number of states, alive and dead included, at least 2
+    pub states: u8,
+}
+
+impl GenerationsRule {
+    /// cell value of `state`, 0 being alive (1.0) and `states - 1` dead (0.0), the dying states
+    /// fading in between
+    pub fn value(&self, state: u8) -> Cell {
+        1.0 - state as Cell / (self.states - 1) as Cell
+    }
+
+    /// state of the cell value, the nearest one for values in between
+    pub fn state(&self, value: Cell) -> u8 {
+        ((1.0 - value.clamp(0.0, 1.0)) * (self.states - 1) as Cell).round() as u8
+    }
+}
+
+impl FromStr for GenerationsRule {
+    type Err = String;
+
+    fn from_str(rule: &str) -> Result<Self, Self::Err> {
+        let (life, states) = rule
+            .trim()
+            .rsplit_once('/')
+            .ok_or_else(|| format!("rule \"{rule}\" is not in B/S/C notation (e.g. B2/S/3)"))?;
+        let states = states.strip_prefix(['C', 'c']).unwrap_or(states);
+        match states.parse() {
+            Ok(states @ 2..) => Ok(Self {
+                life: life.parse()?,
+                states,
+            }),
+            _ => Err(format!(
+                "invalid state count \"{states}\" in \"{rule}\", expected 2 to 255"
+            )),
+        }
+    }
+}
+
+/// Lenia convolution weights, precomputed once per radius and ring peaks
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Kernel {
+    /// (horizontal, vertical) in cells width
+    pub radius: (usize, usize),
+    /// see [`KernelSpec::peaks`]
+    pub peaks: Vec<f64>,
+    /// see [`Lenia::is_kernel_quantized`]
+    pub is_quantized: bool,
+    /// see [`Lenia::kernel_alpha`]
+    pub alpha: f64,
+    /// (raw offset, col offset, weight) of every neighbour with a nonzero weight, the weights
+    /// summing to 1 so that the convolution directly yields the potential distribution; shared
+    /// rather than copied by every step that reads it, and only rebuilt when the kernel changes
+    pub weights: Arc<OffsetWeights>,
+    /// the weights were given as is (e.g. `Lenia::load_kernel_image`), so the table is kept
+    /// whatever the radius and peaks
+    pub is_custom: bool,
+}
+
+impl Kernel {
+    /// the kernel covers the ellipse of semi-axes `radius`, a disc when both radii are equal; a
+    /// radius too small for any neighbour to land on a ring (e.g. 1) weighs them all equally, and
+    /// a 0 radius (e.g. a small `relative_radius` rounded down) is taken as 1
+    pub fn new(radius: (usize, usize), peaks: &[f64], is_quantized: bool, alpha: f64) -> Self {
+        let radius = (radius.0.max(1), radius.1.max(1));
+        let mut weights = vec![];
+        let mut neighbours = vec![];
+        let (radius_x, radius_y) = (radius.0 as isize, radius.1 as isize);
+        for draw in -radius_y..=radius_y {
+            for dcol in -radius_x..=radius_x {
+                if (draw, dcol) == (0, 0) {
+                    continue;
+                }
+                let (nx, ny) = (dcol as f64 / radius.0 as f64, draw as f64 / radius.1 as f64);
+                let normalized_distance = (nx * nx + ny * ny).sqrt();
+                if normalized_distance > 1.0 {
+                    continue;
+                }
+                neighbours.push((draw, dcol, 1.0));
+                let weight = kernel_core_function(normalized_distance, peaks, is_quantized, alpha);
+                if weight > 0.0 {
+                    weights.push((draw, dcol, weight));
+                }
+            }
+        }
+        if weights.is_empty() {
+            weights = neighbours;
+        }
+
+        let total_weight: f64 = weights.iter().map(|&(_, _, weight)| weight).sum();
+        for (_, _, weight) in weights.iter_mut() {
+            *weight /= total_weight;
+        }
+        Self {
+            radius,
+            peaks: peaks.to_vec(),
+            is_quantized,
+            alpha,
+            weights: Arc::new(weights),
+            is_custom: false,
+        }
+    }
+
+    /// check the invariants of the table: weights summing to 1 and, unless custom, symmetric
+    /// under a half turn and mirrors, and under swapping the axes for a disc
+    pub fn self_check(&self) -> Result<(), String> {
+        const TOLERANCE: f64 = 1e-9;
+        if self.weights.is_empty() {
+            return Err(format!(
+                "no neighbour has a nonzero weight with a radius of {:?}",
+                self.radius
+            ));
+        }
+        let total_weight: f64 = self.weights.iter().map(|&(_, _, weight)| weight).sum();
+        if (total_weight - 1.0).abs() > TOLERANCE {
+            return Err(format!("the weights sum to {total_weight} instead of 1"));
+        }
+        if self.is_custom {
+            return Ok(());
+        }
+        let weights: AHashMap<(isize, isize), f64> = self
+            .weights
+            .iter()
+            .map(|&(draw, dcol, weight)| ((draw, dcol), weight))
+            .collect();
+        for (&(draw, dcol), &weight) in &weights {
+            let mut images = vec![
+                ((-draw, -dcol), "half turn"),
+                ((draw, -dcol), "left-right mirror"),
+                ((-draw, dcol), "top-bottom mirror"),
+            ];
+            if self.radius.0 == self.radius.1 {
+                images.push(((dcol, draw), "transpose"));
+            }
+            for (image, symmetry) in images {
+                let image_weight = weights.get(&image).copied().unwrap_or(0.0);
+                if (image_weight - weight).abs() > TOLERANCE * weight.max(image_weight) {
+                    return Err(format!(
+                        "the weight at (raw, col) offset {:?} is {weight}, but {image_weight} at \
+                         its {symmetry} {image:?}",
+                        (draw, dcol)
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// `weights` on a torus of (width, height) `period`, so that each distinct neighbour counts
+    /// exactly once: offsets wrapping onto the same cell are merged into the nearest to the
+    /// center, the ones wrapping onto the center are dropped, and the rest are normalized again;
+    /// the table itself when the torus is large enough for nothing to wrap
+    pub fn unique_weights(&self, (period_w, period_h): (usize, usize)) -> Arc<OffsetWeights> {
+        if 2 * self.radius.0 < period_w && 2 * self.radius.1 < period_h {
+            return Arc::clone(&self.weights);
+        }
+        let (period_w, period_h) = (period_w as isize, period_h as isize);
+        let mut nearest: AHashMap<(isize, isize), (isize, isize, f64)> = AHashMap::new();
+        for &(draw, dcol, weight) in self.weights.iter() {
+            let cell = (draw.rem_euclid(period_h), dcol.rem_euclid(period_w));
+            if cell == (0, 0) {
+                continue;
+            }
+            let distance = |(draw, dcol, _): (isize, isize, f64)| draw * draw + dcol * dcol;
+            nearest
+                .entry(cell)
+                .and_modify(|kept| {
+                    if distance((draw, dcol, weight)) < distance(*kept) {
+                        *kept = (draw, dcol, weight);
+                    }
+                })
+                .or_insert((draw, dcol, weight));
+        }
+        let mut weights: Vec<_> = nearest.into_values().collect();
+        // the order of a hash map isn't stable, and the order of the sum changes its rounding
+        weights.sort_by_key(|&(draw, dcol, _)| (draw, dcol));
+        let total_weight: f64 = weights.iter().map(|&(_, _, weight)| weight).sum();
+        for (_, _, weight) in weights.iter_mut() {
+            *weight /= total_weight;
+        }
+        Arc::new(weights)
+    }
+}
+
+/// one kernel-growth pair of multi-kernel Lenia, see [`Lenia::kernels`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KernelSpec {
+    /// fraction of [`Lenia::kernel_radius`]
+    pub relative_radius: f64,
+    /// height of each concentric ring of the kernel shell, from the center outwards
+    pub peaks: Vec<f64>,
+    /// potential distribution yielding the most growth
+    pub mu: f64,
+    /// width of the growth bell
+    pub sigma: f64,
+    /// share of this pair in the total growth
+    pub weight: f64,
+    /// rebuilt whenever it doesn't match the radius or the peaks anymore
+    #[serde(skip)]
+    pub table: Kernel,
+}
+
+impl Default for KernelSpec {
+    /// the single kernel of classic Lenia
+    fn default() -> Self {
+        Self {
+            relative_radius: 1.0,
+            peaks: vec![1.0],
+            mu: 0.31,
+            sigma: 0.049,
+            weight: 1.0,
+            table: Kernel::default(),
+        }
+    }
+}
+
+impl KernelSpec {
+    /// (horizontal, vertical) in cells width, at least 1, as [`Kernel::new`] takes it
+    pub fn radius(&self, (radius_x, radius_y): (usize, usize)) -> (usize, usize) {
+        (
+            ((radius_x as f64 * self.relative_radius).round() as usize).max(1),
+            ((radius_y as f64 * self.relative_radius).round() as usize).max(1),
+        )
+    }
+
+    pub fn growth(&self, potential_distribution: f64) -> f64 {
+        growth_function(potential_distribution, self.mu, self.sigma)
+    }
+}
+
+/// what a Lenia step depends on besides the cells, see [`next_frame`] and [`Lenia::sim_params`]
+#[derive(Debug, Clone, Copy)]
+pub struct SimParams<'a> {
+    pub delta_t: f64,
+    pub saturation: Saturation,
+    pub potential: Potential,
+    pub boundary: Boundary,
+    /// their tables must already be built for the current radius and peaks
+    pub kernels: &'a [KernelSpec],
+    /// see [`Lenia::skip_empty_regions`]
+    pub skip_empty_regions: bool,
+}
+
+/// cell by cell comparison of two grids, see [`Lenia::diff`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GridDiff {
+    /// (raw, col) of every cell differing by more than the epsilon
+    pub differing_cells: Vec<(usize, usize)>,
+    pub max_abs_difference: Cell,
+    /// over every cell, differing or not
+    pub mean_abs_difference: Cell,
+}
+
+/// what [`Lenia::save_state`] writes
+#[derive(Serialize, Deserialize)]
+struct SavedState {
+    generation: u64,
+    /// of [`Lenia::rng`], bit-cast as TOML integers are signed
+    rng_state: i64,
+    cells: Grid,
+}
+
+/// the lowest and highest cells, see [`Lenia::cell_extremes`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CellExtremes {
+    pub min: Cell,
+    /// (raw, col) of the first cell at `min`, in reading order
+    pub min_cell: (usize, usize),
+    pub max: Cell,
+    /// (raw, col) of the first cell at `max`, in reading order
+    pub max_cell: (usize, usize),
+}
+
+/// spots the grid coming back to one of its recent states, i.e. an oscillator or a still life,
+/// by the hashes of its last frames, see [`Lenia::period_detector`]; Lenia cells seldom repeat
+/// exactly, it's meant for Game of Life and Generations
+#[derive(Debug, Clone)]
+pub struct PeriodDetector {
+    /// hashes of the last consecutive frames, the newest last
+    hashes: VecDeque<u64>,
+    capacity: usize,
+    hash_state: ahash::RandomState,
+    /// number of steps since the last frame was previously seen, 1 for a still life, `None` while
+    /// it matches none of the remembered frames
+    pub period: Option<usize>,
+}
+
+impl PeriodDetector {
+    /// remembering the last `capacity` frames, the longest period it can find
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            hashes: VecDeque::with_capacity(capacity + 1),
+            capacity,
+            hash_state: ahash::RandomState::new(),
+            period: None,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.hashes.clear();
+        self.period = None;
+    }
+
+    fn hash(&self, cells: &[Vec<Cell>]) -> u64 {
+        let mut hasher = self.hash_state.build_hasher();
+        (cells.len(), cells.first().map_or(0, Vec::len)).hash(&mut hasher);
+        for &cell in cells.iter().flatten() {
+            cell.to_bits().hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+
+    /// record the step from `previous` to `cells` and update `period`; the history starts over
+    /// when `previous` isn't the last recorded frame, since an edit, resize or reseed in between
+    /// isn't part of the evolution
+    pub fn record(&mut self, previous: &[Vec<Cell>], cells: &[Vec<Cell>]) -> Option<usize> {
+        let previous_hash = self.hash(previous);
+        if self.hashes.back() != Some(&previous_hash) {
+            self.hashes.clear();
+            self.hashes.push_back(previous_hash);
+        }
+        let hash = self.hash(cells);
+        self.period = self
+            .hashes
+            .iter()
+            .rev()
+            .position(|&seen| seen == hash)
+            .map(|steps_back| steps_back + 1);
+        self.hashes.push_back(hash);
+        if self.hashes.len() > self.capacity {
+            self.hashes.pop_front();
+        }
+        self.period
+    }
+}
+
+/// scale the kernel radius with the grid, see [`Lenia::adaptive_kernel_radius`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdaptiveKernelRadius {
+    /// kernel radius over the smaller grid dimension
+    pub ratio: f64,
+    pub min_radius: usize,
+    pub max_radius: usize,
+}
+
+impl Default for AdaptiveKernelRadius {
+    fn default() -> Self {
+        // 13 cells on the default 128x128 grid
+        Self {
+            ratio: 13.0 / 128.0,
+            min_radius: 3,
+            max_radius: 40,
+        }
+    }
+}
+
+impl AdaptiveKernelRadius {
+    pub fn radius_for(&self, wcell_count: usize, hcell_count: usize) -> usize {
+        ((wcell_count.min(hcell_count) as f64 * self.ratio).round() as usize)
+            .clamp(self.min_radius, self.max_radius)
+    }
+}
+
+pub struct Lenia {
+    pub cells: Grid,
+    /// number of consecutive steps each cell has been above `age_threshold`
+    pub age: Vec<Vec<u32>>,
+    pub age_threshold: Cell,
+    /// Game of Life cells that may change on the next step: the ones that changed on the last
+    /// step or were edited, and their neighbours; every step rebuilds it from its own changes
+    /// only, so it never keeps stale cells and is empty the step after the grid settles into
+    /// still lifes
+    pub active_cells: AHashSet<(usize, usize)>,
+    /// the next Game of Life step ignores `active_cells` and scans every cell, to be set after
+    /// editing `cells` directly
+    pub scan_all_cells: bool,
+    /// Game of Life cells frozen at their current value, still counted by their neighbours
+    pub walls: AHashSet<(usize, usize)>,
+    pub mode: Mode,
+    pub gol_rule: GolRule,
+    pub delta_t: f64,
+    /// how Lenia cells pushed out of [0, 1] by a step are brought back
+    pub saturation: Saturation,
+    pub potential: Potential,
+    /// every random draw of the simulation (seeding, noise) goes through it
+    pub rng: fastrand::Rng,
+    /// (horizontal, vertical) in cells width, scaled by the relative radius of each of `kernels`
+    pub kernel_radius: (usize, usize),
+    /// kernel-growth pairs whose growths are averaged by weight before the `delta_t` integration,
+    /// a single one being classic Lenia; must not be empty
+    pub kernels: Vec<KernelSpec>,
+    /// sample the kernel shell at the normalized distance rounded to a tenth, as Lenia used to:
+    /// the kernel then looks like stepped rings instead of a smooth bell, which slightly changes
+    /// which patterns survive
+    pub is_kernel_quantized: bool,
+    /// sharpness of the kernel bump, higher values narrow each ring
+    pub kernel_alpha: f64,
+    /// number of steps computed since creation
+    pub generation: u64,
+    /// the cells before the last step, for [`Lenia::undo_step`]; edits leave it as it was
+    pub previous_cells: Option<Grid>,
+    /// when set, `kernel_radius` follows the grid size on every resize
+    pub adaptive_kernel_radius: Option<AdaptiveKernelRadius>,
+    pub resize_anchor: ResizeAnchor,
+    /// the edges that wrap around, in every mode but [`Mode::Custom`]
+    pub boundary: Boundary,
+    /// counted by the Game of Life and Generations rules
+    pub neighbourhood: Neighbourhood,
+    /// of the Game of Life cells within a step
+    pub update_order: UpdateOrder,
+    /// Lenia only: rescale the grid after every step to keep the total mass of the previous frame,
+    /// this changes the dynamics
+    pub conserve_mass: bool,
+    /// Lenia only: rescale the grid after every step to this total mass instead, e.g. to keep a
+    /// creature from fading out or filling the grid; takes over `conserve_mass`
+    pub target_mass: Option<f64>,
+    /// when set, every step looks for the period of the pattern
+    pub period_detector: Option<PeriodDetector>,
+    /// total mass change of the last step (after normalization)
+    pub mass_drift: f64,
+    /// clamp `kernel_radius` before every Lenia step so that the kernel fits in the grid, see
+    /// [`Lenia::clamp_kernel_radius`]
+    pub enforce_min_grid: bool,
+    /// give the Lenia cells without any living cell within the kernel radius the growth of a
+    /// zero potential instead of summing their neighbourhood, which gives the same cells a lot
+    /// faster on a sparse grid
+    pub skip_empty_regions: bool,
+}
+
+impl Lenia {
+    /// `spawn_area` is the soup randomized at first, a random area drawn from the instance
+    /// generator when `None`, see [`generate_spawn_area`]; the grid is never under
+    /// [`MIN_GRID_SIZE`] in either dimension
+    pub fn new(
+        (wcell_count, hcell_count): (usize, usize),
+        spawn_area: Option<((usize, usize), (usize, usize))>,
+        mode: Option<Mode>,
+        delta_t: Option<f64>,
+        kernel_radius: Option<usize>,
+    ) -> Self {
+        // derived from the global generator, so `fastrand::seed` makes the whole instance reproducible
+        Self::with_rng(
+            (wcell_count, hcell_count),
+            spawn_area,
+            mode,
+            delta_t,
+            kernel_radius,
+            fastrand::Rng::new(),
+        )
+    }
+
+    /// see [`Lenia::new`], every random draw going through `rng`
+    pub(crate) fn with_rng(
+        (wcell_count, hcell_count): (usize, usize),
+        spawn_area: Option<((usize, usize), (usize, usize))>,
+        mode: Option<Mode>,
+        delta_t: Option<f64>,
+        kernel_radius: Option<usize>,
+        mut rng: fastrand::Rng,
+    ) -> Self {
+        let (wcell_count, hcell_count) = (
+            wcell_count.max(MIN_GRID_SIZE),
+            hcell_count.max(MIN_GRID_SIZE),
+        );
+        let mode = mode.unwrap_or(Mode::Lenia);
+        let spawn_area =
+            spawn_area.unwrap_or_else(|| generate_spawn_area(wcell_count, hcell_count, &mut rng));
+        let mut cells = vec![vec![0.0; wcell_count]; hcell_count];
+        seed_area(&mut cells, &mode, &mut rng, spawn_area, 1.0);
+
+        Self {
+            age: vec![vec![0; wcell_count]; hcell_count],
+            age_threshold: 0.5,
+            cells,
+            active_cells: AHashSet::new(),
+            scan_all_cells: true,
+            walls: AHashSet::new(),
+            mode,
+            gol_rule: GolRule::default(),
+            delta_t: delta_t.unwrap_or(DEFAULT_DELTA_T),
+            saturation: Saturation::default(),
+            potential: Potential::default(),
+            rng,
+            kernel_radius: (
+                kernel_radius.unwrap_or(DEFAULT_KERNEL_RADIUS),
+                kernel_radius.unwrap_or(DEFAULT_KERNEL_RADIUS),
+            ),
+            kernels: vec![KernelSpec::default()],
+            is_kernel_quantized: false,
+            kernel_alpha: 4.0,
+            generation: 0,
+            previous_cells: None,
+            adaptive_kernel_radius: None,
+            resize_anchor: ResizeAnchor::default(),
+            boundary: Boundary::default(),
+            neighbourhood: Neighbourhood::default(),
+            update_order: UpdateOrder::default(),
+            conserve_mass: false,
+            target_mass: None,
+            period_detector: None,
+            mass_drift: 0.0,
+            enforce_min_grid: false,
+            skip_empty_regions: true,
+        }
+    }
+
+    /// resize the grid, never under [`MIN_GRID_SIZE`] in either dimension
+    pub fn resize(&mut self, new_cell_width_count: usize, new_cell_height_count: usize) {
+        let (new_cell_width_count, new_cell_height_count) = (
+            new_cell_width_count.max(MIN_GRID_SIZE),
+            new_cell_height_count.max(MIN_GRID_SIZE),
+        );
+        let (w, h) = (self.cells[0].len(), self.cells.len());
+        if (w, h) == (new_cell_width_count, new_cell_height_count) {
+            return;
+        }
+        if let Some(adaptive_kernel_radius) = self.adaptive_kernel_radius {
+            let radius =
+                adaptive_kernel_radius.radius_for(new_cell_width_count, new_cell_height_count);
+            self.kernel_radius = (radius, radius);
+        }
+        // cells moved or vanished, GoL has to rescan the whole grid
+        self.scan_all_cells = true;
+        self.previous_cells = None;
+
+        // half of the size change goes on each side (the odd cell on the right/bottom) when
+        // centered, so shrinking then growing back by the same amount is lossless for the kept area
+        let (pad_left, pad_top) = match self.resize_anchor {
+            ResizeAnchor::TopLeft => (0, 0),
+            ResizeAnchor::Center => (
+                (new_cell_width_count as isize - w as isize).div_euclid(2),
+                (new_cell_height_count as isize - h as isize).div_euclid(2),
+            ),
+        };
+        self.walls = self
+            .walls
+            .iter()
+            .filter_map(|&(raw, col)| {
+                let (raw, col) = (raw as isize + pad_top, col as isize + pad_left);
+                ((0..new_cell_height_count as isize).contains(&raw)
+                    && (0..new_cell_width_count as isize).contains(&col))
+                .then_some((raw as usize, col as usize))
+            })
+            .collect();
+
+        let new_size = (new_cell_width_count, new_cell_height_count);
+        self.cells = resized_grid(&self.cells, new_size, (pad_left, pad_top));
+        self.age = resized_grid(&self.age, new_size, (pad_left, pad_top));
+    }
+
+    /// advance the simulation by `frames` steps, without any rendering
+    pub fn run(&mut self, frames: usize) {
+        for _ in 0..frames {
+            self.compute_next_frame();
+        }
+    }
+
+    /// sum of all cell values
+    pub fn total_mass(&self) -> f64 {
+        self.cells.iter().flatten().map(|&cell| cell as f64).sum()
+    }
+
+    /// the lowest and highest cell values and where they are, e.g. to spot cells stuck at 1
+    pub fn cell_extremes(&self) -> CellExtremes {
+        let mut extremes = CellExtremes {
+            min: self.cells[0][0],
+            min_cell: (0, 0),
+            max: self.cells[0][0],
+            max_cell: (0, 0),
+        };
+        for (raw, cells) in self.cells.iter().enumerate() {
+            for (col, &cell) in cells.iter().enumerate() {
+                if cell < extremes.min {
+                    extremes.min = cell;
+                    extremes.min_cell = (raw, col);
+                }
+                if cell > extremes.max {
+                    extremes.max = cell;
+                    extremes.max_cell = (raw, col);
+                }
+            }
+        }
+        extremes
+    }
+
+    /// mark (or unmark when `!is_wall`) every cell under the brush as a wall, see [`Lenia::walls`]
+    pub fn paint_walls(
+        &mut self,
+        center: (usize, usize),
+        shape: BrushShape,
+        radius: usize,
+        is_wall: bool,
+    ) {
+        let grid_size = (self.cells[0].len(), self.cells.len());
+        for (position @ (raw, col), _) in brush_positions(center, shape, radius, grid_size) {
+            if is_wall {
+                self.walls.insert(position);
+            } else {
+                self.walls.remove(&position);
+            }
+            self.activate_around(raw, col);
+        }
+    }
+
+    /// blend `value` into every cell under the brush centered on (raw, col), wrapping around the
+    /// edges
+    ///
+    /// with `falloff` the value fades linearly from `value` at the center to zero just past the rim,
+    /// and when overwriting only ever raises cells so that overlapping dabs of a stroke blend
+    /// smoothly
+    pub fn paint(
+        &mut self,
+        center: (usize, usize),
+        shape: BrushShape,
+        radius: usize,
+        value: Cell,
+        falloff: bool,
+        blend: DrawBlend,
+    ) {
+        let grid_size = (self.cells[0].len(), self.cells.len());
+        for ((raw, col), (draw, dcol)) in brush_positions(center, shape, radius, grid_size) {
+            let value = if falloff {
+                let distance = ((draw * draw + dcol * dcol) as Cell).sqrt();
+                value * (1.0 - distance / (radius + 1) as Cell)
+            } else {
+                value
+            };
+            let cell = &mut self.cells[raw][col];
+            *cell = match blend {
+                DrawBlend::Overwrite if falloff => cell.max(value),
+                DrawBlend::Overwrite => value,
+                DrawBlend::Add => (*cell + value).min(1.0),
+                DrawBlend::Subtract => (*cell - value).max(0.0),
+            };
+            self.activate_around(raw, col);
+        }
+    }
+
+    /// set the cells of ((area_w_min, area_w_max), (area_h_min, area_h_max)), bounds included and
+    /// clamped to the grid, to `value`
+    pub fn fill_rect(&mut self, area: ((usize, usize), (usize, usize)), value: Cell) {
+        let (w, h) = (self.cells[0].len(), self.cells.len());
+        let ((area_w_min, area_w_max), (area_h_min, area_h_max)) = area;
+        for raw in area_h_min..=area_h_max.min(h - 1) {
+            for col in area_w_min..=area_w_max.min(w - 1) {
+                self.cells[raw][col] = value;
+                self.activate_around(raw, col);
+            }
+        }
+    }
+
+    /// kill the cells of the area, see [`Lenia::fill_rect`]
+    pub fn clear_rect(&mut self, area: ((usize, usize), (usize, usize))) {
+        self.fill_rect(area, 0.0);
+    }
+
+    /// set the `thickness` outermost cells of the area to `value`, see [`Lenia::fill_rect`]; a
+    /// live border around the grid approximates fixed boundaries in Game of Life
+    pub fn draw_border(
+        &mut self,
+        area: ((usize, usize), (usize, usize)),
+        thickness: usize,
+        value: Cell,
+    ) {
+        if thickness == 0 {
+            return;
+        }
+        let ((area_w_min, area_w_max), (area_h_min, area_h_max)) = area;
+        let inset = thickness - 1;
+        for side in [
+            // top, bottom, left, right
+            ((area_w_min, area_w_max), (area_h_min, area_h_min + inset)),
+            (
+                (area_w_min, area_w_max),
+                (area_h_max.saturating_sub(inset), area_h_max),
+            ),
+            ((area_w_min, area_w_min + inset), (area_h_min, area_h_max)),
+            (
+                (area_w_max.saturating_sub(inset), area_w_max),
+                (area_h_min, area_h_max),
+            ),
+        ] {
+            self.fill_rect(side, value);
+        }
+    }
+
+    /// copy the grid, raw after raw, into `buffer` which must hold exactly width * height cells,
+    /// as `f32` whatever the [`Cell`] type
+    pub fn copy_cells_into(&self, buffer: &mut [f32]) {
+        let w = self.cells[0].len();
+        assert_eq!(buffer.len(), w * self.cells.len(), "buffer size mismatch");
+        for (chunk, raw) in buffer.chunks_exact_mut(w).zip(&self.cells) {
+            for (value, &cell) in chunk.iter_mut().zip(raw) {
+                *value = cell as f32;
+            }
+        }
+    }
+
+    /// write the grid as a 2D (height, width) float32 (float64 with the `f64-cells` feature) NumPy
+    /// `.npy` file, loadable with `numpy.load`
+    pub fn save_npy(&self, path: &Path) -> std::io::Result<()> {
+        let (w, h) = (self.cells[0].len(), self.cells.len());
+        let cell_size = std::mem::size_of::<Cell>();
+        let mut header =
+            format!("{{'descr': '<f{cell_size}', 'fortran_order': False, 'shape': ({h}, {w}), }}");
+        // magic (6) + version (2) + header length (2) + header must be a multiple of 64 bytes,
+        // the header being padded with spaces and terminated by a newline
+        let unpadded_len = 10 + header.len() + 1;
+        header.push_str(&" ".repeat((64 - unpadded_len % 64) % 64));
+        header.push('\n');
+
+        let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        file.write_all(b"\x93NUMPY\x01\x00")?;
+        file.write_all(&(header.len() as u16).to_le_bytes())?;
+        file.write_all(header.as_bytes())?;
+        for &cell in self.cells.iter().flatten() {
+            file.write_all(&cell.to_le_bytes())?;
+        }
+        file.flush()
+    }
+
+    /// replace the grid by a 2D float32 or float64 NumPy `.npy` file as written by
+    /// [`Lenia::save_npy`] (version 1, C order), the grid taking its shape; walls out of it are
+    /// dropped
+    pub fn load_npy(&mut self, path: &Path) -> std::io::Result<()> {
+        let invalid = |message: &str| {
+            std::io::Error::new(std::io::ErrorKind::InvalidData, message.to_string())
+        };
+        let bytes = std::fs::read(path)?;
+        let header_len = match bytes.get(..10) {
+            Some(&[0x93, b'N', b'U', b'M', b'P', b'Y', 1, _, low, high]) => {
+                u16::from_le_bytes([low, high]) as usize
+            }
+            _ => return Err(invalid("not a version 1 .npy file")),
+        };
+        let header = bytes
+            .get(10..10 + header_len)
+            .and_then(|header| std::str::from_utf8(header).ok())
+            .ok_or_else(|| invalid("truncated header"))?;
+        let cell_size = match header {
+            _ if !header.contains("'fortran_order': False") => None,
+            _ if header.contains("'descr': '<f4'") => Some(4),
+            _ if header.contains("'descr': '<f8'") => Some(8),
+            _ => None,
+        }
+        .ok_or_else(|| invalid("expected little-endian float32 or float64 cells in C order"))?;
+        let shape: Option<Vec<usize>> = header
+            .split_once("'shape': (")
+            .and_then(|(_, shape)| shape.split_once(')'))
+            .and_then(|(shape, _)| {
+                shape
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|size| !size.is_empty())
+                    .map(|size| size.parse().ok())
+                    .collect()
+            });
+        let (w, h) = match shape.as_deref() {
+            Some(&[h, w]) if w >= MIN_GRID_SIZE && h >= MIN_GRID_SIZE => (w, h),
+            _ => {
+                return Err(invalid(&format!(
+                    "expected a 2D shape of at least {MIN_GRID_SIZE}x{MIN_GRID_SIZE}"
+                )))
+            }
+        };
+        let data = &bytes[10 + header_len..];
+        if data.len() != w * h * cell_size {
+            return Err(invalid("the data doesn't match the shape"));
+        }
+
+        let parse_cell = |bytes: &[u8]| match bytes.try_into() {
+            Ok(bytes) => f32::from_le_bytes(bytes) as Cell,
+            Err(_) => f64::from_le_bytes(bytes.try_into().unwrap()) as Cell,
+        };
+        self.replace_cells(
+            data.chunks_exact(cell_size * w)
+                .map(|raw| raw.chunks_exact(cell_size).map(parse_cell).collect())
+                .collect(),
+        );
+        Ok(())
+    }
+
+    /// write the grid, the generation and the state of [`Lenia::rng`] as TOML, so that
+    /// [`Lenia::load_state`] resumes the run exactly, random draws included
+    pub fn save_state(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let state = SavedState {
+            generation: self.generation,
+            rng_state: self.rng.get_seed() as i64,
+            cells: self.cells.clone(),
+        };
+        std::fs::write(path, toml::to_string(&state)?)?;
+        Ok(())
+    }
+
+    /// restore a state written by [`Lenia::save_state`], the grid taking its shape as with
+    /// [`Lenia::load_npy`]
+    pub fn load_state(&mut self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let state: SavedState = toml::from_str(&std::fs::read_to_string(path)?)?;
+        let w = state.cells.first().map_or(0, Vec::len);
+        if w < MIN_GRID_SIZE
+            || state.cells.len() < MIN_GRID_SIZE
+            || state.cells.iter().any(|raw| raw.len() != w)
+        {
+            return Err(format!(
+                "expected a grid of at least {MIN_GRID_SIZE}x{MIN_GRID_SIZE} cells, with raws of \
+                 the same length"
+            )
+            .into());
+        }
+        self.replace_cells(state.cells);
+        self.generation = state.generation;
+        self.rng.seed(state.rng_state as u64);
+        Ok(())
+    }
+
+    /// a loaded grid, of any size, in place of the cells
+    fn replace_cells(&mut self, cells: Grid) {
+        let (w, h) = (cells[0].len(), cells.len());
+        self.cells = cells;
+        self.age = vec![vec![0; w]; h];
+        self.walls.retain(|&(raw, col)| raw < h && col < w);
+        self.active_cells.clear();
+        self.scan_all_cells = true;
+        self.previous_cells = None;
+    }
+
+    /// cyclically roll the whole grid by `dx` columns (positive is rightwards) and `dy` raws
+    /// (positive is downwards), in place
+    pub fn shift(&mut self, dx: isize, dy: isize) {
+        let (w, h) = (self.cells[0].len(), self.cells.len());
+        let (dx, dy) = (
+            dx.rem_euclid(w as isize) as usize,
+            dy.rem_euclid(h as isize) as usize,
+        );
+        rotate_grid(&mut self.cells, dx, dy);
+        rotate_grid(&mut self.age, dx, dy);
+        self.walls = self
+            .walls
+            .iter()
+            .map(|&(raw, col)| ((raw + dy) % h, (col + dx) % w))
+            .collect();
+        // positions moved, GoL has to rescan the whole grid
+        self.scan_all_cells = true;
+    }
+
+    /// value at the fractional position (x, y), the center of cell (raw, col) being at (col, raw),
+    /// bilinearly interpolated between the four surrounding cells and wrapping around the edges
+    pub fn sample_bilinear(&self, x: f64, y: f64) -> Cell {
+        let (w, h) = (self.cells[0].len() as isize, self.cells.len() as isize);
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = ((x - x0) as Cell, (y - y0) as Cell);
+        let (col0, raw0) = ((x0 as isize).rem_euclid(w), (y0 as isize).rem_euclid(h));
+        let (col0, col1) = (col0 as usize, ((col0 + 1) % w) as usize);
+        let (raw0, raw1) = (raw0 as usize, ((raw0 + 1) % h) as usize);
+
+        let top = self.cells[raw0][col0] * (1.0 - fx) + self.cells[raw0][col1] * fx;
+        let bottom = self.cells[raw1][col0] * (1.0 - fx) + self.cells[raw1][col1] * fx;
+        top * (1.0 - fy) + bottom * fy
+    }
+
+    /// compare the cells with those of `other`, which must have the same dimensions
+    pub fn diff(&self, other: &Lenia, epsilon: Cell) -> Result<GridDiff, String> {
+        let (w, h) = (self.cells[0].len(), self.cells.len());
+        let (other_w, other_h) = (other.cells[0].len(), other.cells.len());
+        if (w, h) != (other_w, other_h) {
+            return Err(format!(
+                "cannot compare a {w}x{h} grid with a {other_w}x{other_h} one"
+            ));
+        }
+
+        let mut diff = GridDiff::default();
+        let mut total_abs_difference = 0.0;
+        for (raw, (cells, other_cells)) in self.cells.iter().zip(&other.cells).enumerate() {
+            for (col, (&cell, &other_cell)) in cells.iter().zip(other_cells).enumerate() {
+                let abs_difference = (cell - other_cell).abs();
+                if abs_difference > epsilon {
+                    diff.differing_cells.push((raw, col));
+                }
+                diff.max_abs_difference = diff.max_abs_difference.max(abs_difference);
+                total_abs_difference += abs_difference as f64;
+            }
+        }
+        diff.mean_abs_difference = (total_abs_difference / (w * h) as f64) as Cell;
+        Ok(diff)
+    }
+
+    /// every cell minus the one of `reference` at the same place, positive where it grew;
+    /// `None` when `reference` doesn't have the dimensions of the grid
+    pub fn signed_diff(&self, reference: &[Vec<Cell>]) -> Option<Grid> {
+        if reference.len() != self.cells.len()
+            || reference.iter().any(|raw| raw.len() != self.cells[0].len())
+        {
+            return None;
+        }
+        let diff = self
+            .cells
+            .iter()
+            .zip(reference)
+            .map(|(cells, reference_cells)| {
+                cells
+                    .iter()
+                    .zip(reference_cells)
+                    .map(|(cell, reference_cell)| cell - reference_cell)
+                    .collect()
+            })
+            .collect();
+        Some(diff)
+    }
+
+    /// tightest ((col_min, col_max), (raw_min, raw_max)) box holding every cell above `threshold`.
+    ///
+    /// The grid is a torus, so a structure straddling an edge yields a box wrapping around it,
+    /// in that case min > max (e.g. cols (250, 5)); `None` when no cell is above the threshold
+    pub fn live_bounds(&self, threshold: Cell) -> Option<((usize, usize), (usize, usize))> {
+        let w = self.cells[0].len();
+        let mut occupied_cols = vec![false; w];
+        let mut occupied_raws = vec![false; self.cells.len()];
+        for (raw, cells) in self.cells.iter().enumerate() {
+            for (col, &cell) in cells.iter().enumerate() {
+                if cell > threshold {
+                    occupied_raws[raw] = true;
+                    occupied_cols[col] = true;
+                }
+            }
+        }
+
+        // width and height are independent, so minimizing each one minimizes the area
+        Some((
+            occupied_span(&occupied_cols)?,
+            occupied_span(&occupied_raws)?,
+        ))
+    }
+
+    /// a grid emptied from the outside (e.g. `cells` replaced by an empty one) stays as is
+    pub fn compute_next_frame(&mut self) {
+        if self.cells.first().is_none_or(|cells| cells.is_empty()) {
+            return;
+        }
+        self.previous_cells = Some(self.cells.clone());
+        let mass_before = self.total_mass();
+        match &mut self.mode {
+            Mode::Lenia => self.compute_next_lenia_frame(),
+            Mode::GameOfLife => self.compute_next_gol_frame(),
+            Mode::Generations(rule) => {
+                self.cells =
+                    next_generations_frame(&self.cells, rule, self.neighbourhood, self.boundary)
+            }
+            Mode::Custom(automaton) => {
+                let mut next_frame_cells = self.cells.clone();
+                automaton.step(&self.cells, &mut next_frame_cells);
+                self.cells = next_frame_cells;
+            }
+        }
+        if matches!(self.mode, Mode::Lenia) {
+            if let Some(target_mass) = self.target_mass {
+                self.scale_mass_to(target_mass);
+            } else if self.conserve_mass {
+                self.scale_mass_to(mass_before);
+            }
+        }
+        self.mass_drift = self.total_mass() - mass_before;
+        self.generation += 1;
+        if let (Some(detector), Some(previous)) = (&mut self.period_detector, &self.previous_cells)
+        {
+            detector.record(previous, &self.cells);
+        }
+
+        for (cells, ages) in self.cells.iter().zip(self.age.iter_mut()) {
+            for (&cell, age) in cells.iter().zip(ages.iter_mut()) {
+                *age = if cell > self.age_threshold {
+                    age.saturating_add(1)
+                } else {
+                    0
+                };
+            }
+        }
+    }
+
+    /// switch to `mode` at runtime with its defaults: binary modes threshold the cells at 0.5
+    /// (coming from a Generations automaton only its live cells stay alive, not the dying ones)
+    /// and drop the Lenia mass conservation and target, and Lenia takes the cells as they are
+    /// with [`DEFAULT_DELTA_T`] and [`DEFAULT_KERNEL_RADIUS`] (or the adaptive radius of the
+    /// grid); the kernels and rules are kept
+    pub fn set_mode(&mut self, mode: Mode) {
+        match mode {
+            Mode::GameOfLife | Mode::Generations(_) => {
+                let threshold = match self.mode {
+                    Mode::Generations(_) => 1.0,
+                    _ => 0.5,
+                };
+                for cell in self.cells.iter_mut().flatten() {
+                    *cell = if *cell >= threshold { 1.0 } else { 0.0 };
+                }
+                self.conserve_mass = false;
+                self.target_mass = None;
+            }
+            Mode::Lenia => {
+                self.delta_t = DEFAULT_DELTA_T;
+                let (w, h) = (self.cells[0].len(), self.cells.len());
+                let radius = self
+                    .adaptive_kernel_radius
+                    .map_or(DEFAULT_KERNEL_RADIUS, |adaptive| adaptive.radius_for(w, h));
+                self.kernel_radius = (radius, radius);
+            }
+            Mode::Custom(_) => {}
+        }
+        self.mode = mode;
+        // the ages counted under the old rule mean nothing under the new one
+        for age in self.age.iter_mut().flatten() {
+            *age = 0;
+        }
+        // the mass drift, active cells and previous frame belong to the old rule
+        self.mass_drift = 0.0;
+        self.active_cells.clear();
+        self.scan_all_cells = true;
+        self.previous_cells = None;
+    }
+
+    /// potential distribution of every kernel at (raw, col), as the next Lenia step computes it;
+    /// tables out of date are built for the occasion, leaving the instance as it is so that it can
+    /// be read while rendering
+    pub fn potentials_at(&self, cell: (usize, usize)) -> Vec<f64> {
+        let size = (self.cells[0].len(), self.cells.len());
+        let kernel_radius = match self.enforce_min_grid {
+            true => self.clamped_kernel_radius(),
+            false => self.kernel_radius,
+        };
+        let kernels: Vec<KernelSpec> = self
+            .kernels
+            .iter()
+            .map(|spec| match self.stale_table(spec, kernel_radius) {
+                Some(table) => KernelSpec {
+                    table,
+                    ..spec.clone()
+                },
+                None => spec.clone(),
+            })
+            .collect();
+        let params = SimParams {
+            delta_t: self.delta_t,
+            saturation: self.saturation,
+            potential: self.potential,
+            boundary: self.boundary,
+            kernels: &kernels,
+            skip_empty_regions: self.skip_empty_regions,
+        };
+        let weights = PotentialWeights::new(&params, size);
+        (0..params.kernels.len())
+            .map(|index| weights.potential(index, &self.cells, cell))
+            .collect()
+    }
+
+    /// largest change of a cell over the last step, `None` when there is no step to compare with
+    /// (see [`Lenia::undo_step`])
+    pub fn last_step_change(&self) -> Option<Cell> {
+        let previous_cells = self.previous_cells.as_ref()?;
+        Some(
+            previous_cells
+                .iter()
+                .flatten()
+                .zip(self.cells.iter().flatten())
+                .map(|(&previous, &cell)| (cell - previous).abs())
+                .fold(0.0, Cell::max),
+        )
+    }
+
+    /// go back to the cells before the last step, once: edits made since are lost, and ages keep
+    /// counting; false when there is no step to undo
+    pub fn undo_step(&mut self) -> bool {
+        let Some(previous_cells) = self.previous_cells.take() else {
+            return false;
+        };
+        self.cells = previous_cells;
+        self.generation -= 1;
+        self.scan_all_cells = true;
+        true
+    }
+
+    /// randomize a `density` fraction of the cells of ((area_w_min, area_w_max), (area_h_min,
+    /// area_h_max)), bounds included, killing the others
+    pub fn seed_area(&mut self, area: ((usize, usize), (usize, usize)), density: f32) {
+        seed_area(&mut self.cells, &self.mode, &mut self.rng, area, density);
+        let (w, h) = (self.cells[0].len(), self.cells.len());
+        let ((area_w_min, area_w_max), (area_h_min, area_h_max)) = area;
+        for raw in area_h_min..=area_h_max.min(h - 1) {
+            for col in area_w_min..=area_w_max.min(w - 1) {
+                self.activate_around(raw, col);
+            }
+        }
+    }
+
+    /// kill every cell, then randomize `count` random areas from [`generate_spawn_area`], e.g. to
+    /// seed several potential creatures at once; they may overlap
+    pub fn seed_blobs(&mut self, count: usize) {
+        let (w, h) = (self.cells[0].len(), self.cells.len());
+        for cell in self.cells.iter_mut().flatten() {
+            *cell = 0.0;
+        }
+        for _ in 0..count {
+            let area = generate_spawn_area(w, h, &mut self.rng);
+            seed_area(&mut self.cells, &self.mode, &mut self.rng, area, 1.0);
+        }
+        // GoL has to rescan the whole grid
+        self.scan_all_cells = true;
+    }
+
+    /// kill every cell, then randomize a box of random size centered on the grid and mirror a
+    /// part of it over the rest, e.g. its top-left quadrant with [`Symmetry::Quadrants`], so that
+    /// the soup is symmetric
+    pub fn seed_symmetric(&mut self, symmetry: Symmetry) {
+        let (w, h) = (self.cells[0].len(), self.cells.len());
+        let (col_min, raw_min) = (
+            self.rng.usize(0..w.div_ceil(2)),
+            self.rng.usize(0..h.div_ceil(2)),
+        );
+        for cell in self.cells.iter_mut().flatten() {
+            *cell = 0.0;
+        }
+        seed_area(
+            &mut self.cells,
+            &self.mode,
+            &mut self.rng,
+            ((col_min, w - 1 - col_min), (raw_min, h - 1 - raw_min)),
+            1.0,
+        );
+        for raw in 0..h {
+            for col in 0..w {
+                let (source_raw, source_col) = symmetry.source((raw, col), (w, h));
+                self.cells[raw][col] = self.cells[source_raw][source_col];
+            }
+        }
+        // GoL has to rescan the whole grid
+        self.scan_all_cells = true;
+    }
+
+    /// replace every cell value `v` with `1 - v`, which flips Game of Life cells (walls excepted)
+    pub fn invert(&mut self) {
+        for (raw, cells) in self.cells.iter_mut().enumerate() {
+            for (col, cell) in cells.iter_mut().enumerate() {
+                if !matches!(self.mode, Mode::GameOfLife) || !self.walls.contains(&(raw, col)) {
+                    *cell = 1.0 - *cell;
+                }
+            }
+        }
+        // GoL has to rescan the whole grid
+        self.scan_all_cells = true;
+    }
+
+    /// perturb every cell by a uniform random offset in [-amplitude, amplitude], clamped to [0, 1];
+    /// in Game of Life, where cells are binary, flip a random `amplitude` fraction of the cells
+    /// (walls excepted)
+    pub fn add_noise(&mut self, amplitude: f32) {
+        // only Game of Life has walls
+        let is_gol = matches!(self.mode, Mode::GameOfLife);
+        for (raw, cells) in self.cells.iter_mut().enumerate() {
+            for (col, cell) in cells.iter_mut().enumerate() {
+                match self.mode {
+                    // dying states are flipped too, into the matching state from the other end
+                    Mode::GameOfLife | Mode::Generations(_) => {
+                        let is_wall = is_gol && self.walls.contains(&(raw, col));
+                        if self.rng.f32() < amplitude && !is_wall {
+                            *cell = 1.0 - *cell;
+                        }
+                    }
+                    _ => {
+                        let offset = amplitude * (2.0 * self.rng.f32() - 1.0);
+                        *cell = (*cell + offset as Cell).clamp(0.0, 1.0)
+                    }
+                }
+            }
+        }
+        // GoL has to rescan the whole grid
+        self.scan_all_cells = true;
+    }
+
+    /// randomize a `density` fraction of the cells below `threshold`, leaving the live structures
+    /// as they are, e.g. to put food around a creature; binary modes draw 0 or 1, the others a
+    /// value in [0, 1); Game of Life walls are left as they are too
+    pub fn seed_empty(&mut self, threshold: Cell, density: f32) {
+        let is_gol = matches!(self.mode, Mode::GameOfLife);
+        for (raw, cells) in self.cells.iter_mut().enumerate() {
+            for (col, cell) in cells.iter_mut().enumerate() {
+                if *cell >= threshold
+                    || (is_gol && self.walls.contains(&(raw, col)))
+                    || self.rng.f32() >= density
+                {
+                    continue;
+                }
+                *cell = match self.mode {
+                    Mode::GameOfLife | Mode::Generations(_) => self.rng.usize(0..=1) as Cell,
+                    _ => self.rng.f32() as Cell,
+                };
+            }
+        }
+        // GoL has to rescan the whole grid
+        self.scan_all_cells = true;
+    }
+
+    /// kill every cell, and remove every wall
+    pub fn clear(&mut self) {
+        let (w, h) = (self.cells[0].len(), self.cells.len());
+        self.cells = vec![vec![0.0; w]; h];
+        self.age = vec![vec![0; w]; h];
+        self.walls.clear();
+        // nothing is alive, so nothing can change
+        self.active_cells.clear();
+        self.scan_all_cells = false;
+    }
+
+    /// queue (raw, col) and its neighbours for the next Game of Life step, see
+    /// [`Lenia::active_cells`]
+    fn activate_around(&mut self, raw: usize, col: usize) {
+        if !matches!(self.mode, Mode::GameOfLife) {
+            return;
+        }
+        let (w, h) = (self.cells[0].len(), self.cells.len());
+        self.active_cells.insert((raw, col));
+        for offset in self.neighbourhood.offsets() {
+            if let Some(neighbour) = self.boundary.neighbour((w, h), (raw, col), offset) {
+                self.active_cells.insert(neighbour);
+            }
+        }
+    }
+
+    /// scale every cell so that the total mass becomes `target_mass`; cells saturating at 1.0
+    /// leave their share to the others, so it's only missed when every living cell is full
+    pub fn scale_mass_to(&mut self, target_mass: f64) {
+        let mass = self.total_mass();
+        if mass <= 0.0 {
+            return;
+        }
+        if target_mass <= mass {
+            let factor = (target_mass / mass) as Cell;
+            for cell in self.cells.iter_mut().flatten() {
+                *cell *= factor;
+            }
+            return;
+        }
+        // every pass saturates at least one more cell or is the last
+        loop {
+            let (mut full_mass, mut free_mass) = (0.0, 0.0);
+            for &cell in self.cells.iter().flatten() {
+                if cell >= 1.0 {
+                    full_mass += 1.0;
+                } else {
+                    free_mass += cell as f64;
+                }
+            }
+            if free_mass <= 0.0 {
+                return;
+            }
+            let factor = ((target_mass - full_mass) / free_mass) as Cell;
+            let mut saturated = false;
+            for cell in self.cells.iter_mut().flatten().filter(|cell| **cell < 1.0) {
+                *cell *= factor;
+                if *cell >= 1.0 {
+                    *cell = 1.0;
+                    saturated = true;
+                }
+            }
+            if !saturated {
+                return;
+            }
+        }
+    }
+
+    /// draw `mu`, `sigma`, `kernel_radius` and `delta_t` from the instance generator, within the
+    /// ranges of the known Lenia creatures, back to a single circular kernel
+    pub fn randomize_params(&mut self) {
+        let mu = 0.1 + 0.3 * self.rng.f64();
+        // growth bells much wider than a fifth of mu either feed or starve everything
+        let sigma = mu * (0.05 + 0.15 * self.rng.f64());
+        let radius = self.rng.usize(8..=20).min(self.max_kernel_radius());
+        self.kernel_radius = (radius, radius);
+        self.delta_t = [0.05, 0.1, 0.2, 0.5][self.rng.usize(0..4)];
+        self.kernels = vec![KernelSpec {
+            mu,
+            sigma,
+            ..KernelSpec::default()
+        }];
+    }
+
+    /// largest kernel radius whose neighbourhood doesn't wrap onto itself: `min(w, h) / 2 - 1`,
+    /// at least 1
+    pub fn max_kernel_radius(&self) -> usize {
+        let (w, h) = (self.cells[0].len(), self.cells.len());
+        (w.min(h) / 2).saturating_sub(1).max(1)
+    }
+
+    /// clamp both `kernel_radius` axes to [`Lenia::max_kernel_radius`], a kernel wider than the
+    /// grid counting the same cells from several sides; true when the radius changed (kernel
+    /// pairs with a relative radius above 1 can still wrap)
+    pub fn clamp_kernel_radius(&mut self) -> bool {
+        let radius = self.kernel_radius;
+        self.kernel_radius = self.clamped_kernel_radius();
+        self.kernel_radius != radius
+    }
+
+    /// `kernel_radius` as [`Lenia::clamp_kernel_radius`] leaves it
+    fn clamped_kernel_radius(&self) -> (usize, usize) {
+        let max_radius = self.max_kernel_radius();
+        let (radius_x, radius_y) = self.kernel_radius;
+        (radius_x.min(max_radius), radius_y.min(max_radius))
+    }
+
+    /// move both `kernel_radius` axes by `steps` cells within 1..=[`Lenia::max_kernel_radius`],
+    /// rebuilding the kernel tables right away; the new radius
+    pub fn step_kernel_radius(&mut self, steps: isize) -> (usize, usize) {
+        let max_radius = self.max_kernel_radius();
+        let step = |radius: usize| radius.saturating_add_signed(steps).clamp(1, max_radius);
+        self.kernel_radius = (step(self.kernel_radius.0), step(self.kernel_radius.1));
+        self.update_kernel_tables();
+        self.kernel_radius
+    }
+
+    /// rebuild the kernel tables that don't match the radius, peaks, quantization or alpha anymore
+    fn update_kernel_tables(&mut self) {
+        if self.enforce_min_grid {
+            self.clamp_kernel_radius();
+        }
+        for index in 0..self.kernels.len() {
+            if let Some(table) = self.stale_table(&self.kernels[index], self.kernel_radius) {
+                self.kernels[index].table = table;
+            }
+        }
+    }
+
+    /// the table `spec` gets with `kernel_radius` and the current peaks, quantization and alpha,
+    /// `None` when its own one is up to date (or custom)
+    fn stale_table(&self, spec: &KernelSpec, kernel_radius: (usize, usize)) -> Option<Kernel> {
+        let radius = spec.radius(kernel_radius);
+        let table = &spec.table;
+        let is_stale = !table.is_custom
+            && (table.radius != radius
+                || table.peaks != spec.peaks
+                || table.is_quantized != self.is_kernel_quantized
+                || table.alpha != self.kernel_alpha);
+        is_stale.then(|| {
+            Kernel::new(
+                radius,
+                &spec.peaks,
+                self.is_kernel_quantized,
+                self.kernel_alpha,
+            )
+        })
+    }
+
+    /// the parameters of the next Lenia step, with up to date kernel tables
+    pub fn sim_params(&mut self) -> SimParams<'_> {
+        self.update_kernel_tables();
+        SimParams {
+            delta_t: self.delta_t,
+            saturation: self.saturation,
+            potential: self.potential,
+            boundary: self.boundary,
+            kernels: &self.kernels,
+            skip_empty_regions: self.skip_empty_regions,
+        }
+    }
+
+    pub fn compute_next_lenia_frame(&mut self) {
+        self.update_kernel_tables();
+        let params = SimParams {
+            delta_t: self.delta_t,
+            saturation: self.saturation,
+            potential: self.potential,
+            boundary: self.boundary,
+            kernels: &self.kernels,
+            skip_empty_regions: self.skip_empty_regions,
+        };
+        self.cells = next_frame(&self.cells, &params);
+    }
+
+    pub fn compute_next_gol_frame(&mut self) {
+        let (w, h) = (self.cells[0].len(), self.cells.len());
+
+        let mut next_frame_cells = self.cells.clone();
+        let mut next_frame_active_cells: AHashSet<(usize, usize)> = AHashSet::new();
+        let offsets: Vec<_> = self.neighbourhood.offsets().collect();
+        let boundary = self.boundary;
+        let neighbours = |(raw, col): (usize, usize)| {
+            offsets
+                .iter()
+                .filter_map(move |&offset| boundary.neighbour((w, h), (raw, col), offset))
+        };
+
+        // (raw, col) from `cells` into `next_cells`, marking it and its neighbours as active
+        // when it changes
+        let update_cell = |cells: &Grid,
+                           next_cells: &mut Grid,
+                           next_active_cells: &mut AHashSet<(usize, usize)>,
+                           (raw, col): (usize, usize)| {
+            if self.walls.contains(&(raw, col)) {
+                return;
+            }
+
+            let is_alive = cells[raw][col] == 1.0;
+            let alive_cells_count = neighbours((raw, col))
+                .map(|(raw, col)| cells[raw][col])
+                .sum::<Cell>() as u8;
+
+            let rule_counts = if is_alive {
+                &self.gol_rule.survive
+            } else {
+                &self.gol_rule.birth
+            };
+            if rule_counts.contains(&alive_cells_count) {
+                next_cells[raw][col] = 1.0;
+            } else {
+                next_cells[raw][col] = 0.0;
+            }
+
+            // change detected, add all affected cells (neighbours and current cells)
+            if cells[raw][col] != next_cells[raw][col] {
+                next_active_cells.insert((raw, col));
+                next_active_cells.extend(neighbours((raw, col)));
+            }
+        };
+
+        match self.update_order {
+            UpdateOrder::Synchronous if self.scan_all_cells => {
+                for raw in 0..h {
+                    for col in 0..w {
+                        update_cell(
+                            &self.cells,
+                            &mut next_frame_cells,
+                            &mut next_frame_active_cells,
+                            (raw, col),
+                        );
+                    }
+                }
+            }
+            UpdateOrder::Synchronous => {
+                for &cell in &self.active_cells {
+                    update_cell(
+                        &self.cells,
+                        &mut next_frame_cells,
+                        &mut next_frame_active_cells,
+                        cell,
+                    );
+                }
+            }
+            UpdateOrder::Checkerboard => {
+                let is_even = |&(raw, col): &(usize, usize)| (raw + col) % 2 == 0;
+                let cells: AHashSet<(usize, usize)> = if self.scan_all_cells {
+                    (0..h)
+                        .flat_map(|raw| (0..w).map(move |col| (raw, col)))
+                        .collect()
+                } else {
+                    self.active_cells.clone()
+                };
+                for &cell in cells.iter().filter(|cell| is_even(cell)) {
+                    update_cell(
+                        &self.cells,
+                        &mut next_frame_cells,
+                        &mut next_frame_active_cells,
+                        cell,
+                    );
+                }
+                // the odd cells next to an even one that just changed are updated as well
+                let halfway_cells = next_frame_cells.clone();
+                let odd_cells: AHashSet<(usize, usize)> = cells
+                    .iter()
+                    .chain(&next_frame_active_cells)
+                    .copied()
+                    .filter(|cell| !is_even(cell))
+                    .collect();
+                for cell in odd_cells {
+                    update_cell(
+                        &halfway_cells,
+                        &mut next_frame_cells,
+                        &mut next_frame_active_cells,
+                        cell,
+                    );
+                }
+            }
+        }
+
+        self.cells = next_frame_cells;
+        self.active_cells = next_frame_active_cells;
+        self.scan_all_cells = false;
+    }
+}
+
+/// smallest circular (min, max) span covering every occupied index, i.e. the complement of the
+/// largest run of unoccupied indices (ties favour a span that doesn't wrap)
+fn occupied_span(occupied: &[bool]) -> Option<(usize, usize)> {
+    let n = occupied.len();
+    let first = occupied.iter().position(|&is_occupied| is_occupied)?;
+
+    let (mut gap, mut largest_gap, mut span) = (0, 0, (0, n - 1));
+    for i in 1..=n {
+        let idx = (first + i) % n;
+        if occupied[idx] {
+            if gap > 0 && gap >= largest_gap {
+                largest_gap = gap;
+                span = (idx, (idx + n - gap - 1) % n);
+            }
+            gap = 0;
+        } else {
+            gap += 1;
+        }
+    }
+    Some(span)
+}
+
+/// kernel shell: concentric rings of height `peaks`, each a smooth bump over the distance from the
+/// cell normalized by the kernel radius, 0 at the center and the rim; `is_quantized` rounds the
+/// distance to a tenth, see [`Lenia::is_kernel_quantized`], and `alpha` sharpens the bump
+pub fn kernel_core_function(
+    normalized_distance: f64,
+    peaks: &[f64],
+    is_quantized: bool,
+    alpha: f64,
+) -> f64 {
+    let r = if is_quantized {
+        (normalized_distance * 10.0).round() / 10.0
+    } else {
+        normalized_distance
+    };
+    let br = r * peaks.len() as f64;
+    let ring = (br as usize).min(peaks.len().saturating_sub(1));
+    let r = br - ring as f64;
+    // the bump tends to 0 at both ends, but evaluating it there divides by zero
+    if r <= 0.0 || r >= 1.0 {
+        return 0.0;
+    }
+    peaks[ring] * (alpha * (1.0 - 1.0 / (alpha * r * (1.0 - r)))).exp()
+}
+
+/// growth in [-1, 1] from the potential distribution (the normalized convolution), a bell
+/// centered on `mu` of width `sigma`
+pub fn growth_function(potential_distribution: f64, mu: f64, sigma: f64) -> f64 {
+    let k = 2.0 * sigma * sigma;
+    let l = (potential_distribution - mu).abs();
+    2.0 * (-(l * l) / k).exp() - 1.0
+}
+
+/// `next = saturation(cells + delta_t * growths)` over a raw, computed in f64 like the potential
+/// distribution
+fn integrate_growth(
+    cells: &[Cell],
+    growths: &[f64],
+    delta_t: f64,
+    saturation: Saturation,
+    next: &mut [Cell],
+) {
+    if saturation == Saturation::Clamp {
+        return integrate_growth_clamped(cells, growths, delta_t, next);
+    }
+    for ((next, &cell), &growth) in next.iter_mut().zip(cells).zip(growths) {
+        *next = saturation.apply(cell as f64 + delta_t * growth) as Cell;
+    }
+}
+
+/// `next = clamp(cells + delta_t * growths, 0, 1)` over a raw
+#[cfg(not(feature = "simd"))]
+fn integrate_growth_clamped(cells: &[Cell], growths: &[f64], delta_t: f64, next: &mut [Cell]) {
+    for ((next, &cell), &growth) in next.iter_mut().zip(cells).zip(growths) {
+        *next = (cell as f64 + delta_t * growth).clamp(0.0, 1.0) as Cell;
+    }
+}
+
+/// `next = clamp(cells + delta_t * growths, 0, 1)` over a raw, 4 cells at a time; the lanes do
+/// the same unfused f64 multiply, add, max and min as the scalar version, so both give the same
+/// values
+#[cfg(feature = "simd")]
+fn integrate_growth_clamped(cells: &[Cell], growths: &[f64], delta_t: f64, next: &mut [Cell]) {
+    use wide::f64x4;
+    const LANES: usize = 4;
+    let (zero, one, delta_t_lanes) = (f64x4::ZERO, f64x4::ONE, f64x4::splat(delta_t));
+    let mut next_chunks = next.chunks_exact_mut(LANES);
+    let mut cell_chunks = cells.chunks_exact(LANES);
+    let mut growth_chunks = growths.chunks_exact(LANES);
+    for ((next, cells), growths) in (&mut next_chunks)
+        .zip(&mut cell_chunks)
+        .zip(&mut growth_chunks)
+    {
+        let cells = f64x4::from([
+            cells[0] as f64,
+            cells[1] as f64,
+            cells[2] as f64,
+            cells[3] as f64,
+        ]);
+        let growths = f64x4::from([growths[0], growths[1], growths[2], growths[3]]);
+        let values = (cells + delta_t_lanes * growths)
+            .max(zero)
+            .min(one)
+            .to_array();
+        for (next, value) in next.iter_mut().zip(values) {
+            *next = value as Cell;
+        }
+    }
+    // the last `len % 4` cells
+    for ((next, &cell), &growth) in next_chunks
+        .into_remainder()
+        .iter_mut()
+        .zip(cell_chunks.remainder())
+        .zip(growth_chunks.remainder())
+    {
+        *next = (cell as f64 + delta_t * growth).clamp(0.0, 1.0) as Cell;
+    }
+}
+
+/// sum of the cells around (raw, col) at the offsets of `weights`, wrapping around the edges of
+/// `boundary`
+fn weighted_sum(
+    cells: &[Vec<Cell>],
+    weights: &[(isize, isize, f64)],
+    (raw, col): (usize, usize),
+    boundary: Boundary,
+) -> f64 {
+    let (w, h) = (cells[0].len(), cells.len());
+    weights
+        .iter()
+        .filter_map(|&(draw, dcol, weight)| {
+            let (ypos, xpos) = boundary.neighbour((w, h), (raw, col), (draw, dcol))?;
+            Some(cells[ypos][xpos] as f64 * weight)
+        })
+        .sum()
+}
+
+/// (raw offset, col offset, weight) of the cells summed around a cell
+pub type OffsetWeights = Vec<(isize, isize, f64)>;
+
+/// uniform weights averaging the disc of `inner_fraction` of
+/// `radius` around a cell, itself included, and the ring between that disc and `radius`, see
+/// [`Potential::InnerOuterRatio`]
+fn inner_outer_weights(radius: (usize, usize), inner_fraction: f64) -> [OffsetWeights; 2] {
+    let (mut inner, mut outer) = (vec![], vec![]);
+    let (radius_x, radius_y) = (radius.0 as isize, radius.1 as isize);
+    for draw in -radius_y..=radius_y {
+        for dcol in -radius_x..=radius_x {
+            let (nx, ny) = (
+                dcol as f64 / radius.0.max(1) as f64,
+                draw as f64 / radius.1.max(1) as f64,
+            );
+            let normalized_distance = (nx * nx + ny * ny).sqrt();
+            if normalized_distance <= inner_fraction {
+                inner.push((draw, dcol, 1.0));
+            } else if normalized_distance <= 1.0 {
+                outer.push((draw, dcol, 1.0));
+            }
+        }
+    }
+    for weights in [&mut inner, &mut outer] {
+        let count = weights.len() as f64;
+        for (_, _, weight) in weights.iter_mut() {
+            *weight /= count;
+        }
+    }
+    [inner, outer]
+}
+
+/// the weights each kernel of a Lenia step sums the neighbourhood with, for a given grid size
+struct PotentialWeights {
+    kernels: Vec<Arc<OffsetWeights>>,
+    /// with [`Potential::InnerOuterRatio`], the inner disc and outer ring of each kernel
+    inner_outer: Vec<Option<[OffsetWeights; 2]>>,
+    boundary: Boundary,
+}
+
+impl PotentialWeights {
+    fn new(params: &SimParams, (w, h): (usize, usize)) -> Self {
+        // a kernel wider than the grid would count some neighbours several times, which can't
+        // happen along an edge that doesn't wrap, as if the grid were larger than the kernel
+        // that way
+        let kernels = params
+            .kernels
+            .iter()
+            .map(|spec| {
+                let (radius_x, radius_y) = spec.table.radius;
+                // across a flipped seam the neighbours only come back to the same cells after
+                // crossing it twice
+                let period = |size: usize, radius: usize, wraps: bool, flips: bool| match wraps {
+                    true if flips => 2 * size,
+                    true => size,
+                    false => size.max(2 * radius + 1),
+                };
+                let boundary = params.boundary;
+                spec.table.unique_weights((
+                    period(w, radius_x, boundary.wrap_x, boundary.flip_x),
+                    period(h, radius_y, boundary.wrap_y, boundary.flip_y),
+                ))
+            })
+            .collect();
+        let inner_outer = params
+            .kernels
+            .iter()
+            .map(|spec| match params.potential {
+                Potential::Kernel => None,
+                Potential::InnerOuterRatio { inner_fraction } => {
+                    Some(inner_outer_weights(spec.table.radius, inner_fraction))
+                }
+            })
+            .collect();
+        Self {
+            kernels,
+            inner_outer,
+            boundary: params.boundary,
+        }
+    }
+
+    /// potential distribution of the kernel at `index` at (raw, col), what its growth is a
+    /// function of
+    fn potential(&self, index: usize, cells: &[Vec<Cell>], cell: (usize, usize)) -> f64 {
+        match &self.inner_outer[index] {
+            None => weighted_sum(cells, &self.kernels[index], cell, self.boundary),
+            // an empty ring would divide by zero
+            Some([inner, outer]) => {
+                weighted_sum(cells, inner, cell, self.boundary)
+                    / weighted_sum(cells, outer, cell, self.boundary).max(f64::MIN_POSITIVE)
+            }
+        }
+    }
+}
+
+/// side (in cells) of the blocks of an [`EmptyRegions`] map
+const EMPTY_REGION_BLOCK_SIZE: usize = 8;
+
+/// the square blocks of a grid whose cells have no nonzero cell within the kernel radius, from a
+/// coarse map of the blocks holding one, see [`Lenia::skip_empty_regions`]
+struct EmptyRegions {
+    /// by block raw then block col
+    is_empty: Vec<Vec<bool>>,
+}
+
+impl EmptyRegions {
+    /// `radius` is the (horizontal, vertical) extent of the widest kernel
+    fn new(cells: &[Vec<Cell>], (radius_x, radius_y): (usize, usize), boundary: Boundary) -> Self {
+        let (w, h) = (cells[0].len(), cells.len());
+        let blocks = |size: usize| size.div_ceil(EMPTY_REGION_BLOCK_SIZE);
+        let mut is_occupied = vec![vec![false; blocks(w)]; blocks(h)];
+        for (raw, cells) in cells.iter().enumerate() {
+            for (col, &cell) in cells.iter().enumerate() {
+                if cell != 0.0 {
+                    is_occupied[raw / EMPTY_REGION_BLOCK_SIZE][col / EMPTY_REGION_BLOCK_SIZE] =
+                        true;
+                }
+            }
+        }
+
+        // the cells along an axis within `radius` of the block at `block`, beyond the grid where
+        // the kernel reaches past its edges
+        let span = |block: usize, radius: usize, size: usize| {
+            let first = (block * EMPTY_REGION_BLOCK_SIZE) as isize - radius as isize;
+            let last = ((block + 1) * EMPTY_REGION_BLOCK_SIZE).min(size) - 1 + radius;
+            (first, last as isize)
+        };
+        let crosses_seam = |(first, last): (isize, isize), size: usize, wraps: bool| {
+            wraps && (first < 0 || last >= size as isize)
+        };
+        // the blocks holding the cells of `span`, and their mirrors across the center too
+        let covered_blocks = |(first, last): (isize, isize), size: usize, wraps, mirrored| {
+            let mut covered = vec![false; blocks(size)];
+            for index in first..=last {
+                let index = match wraps {
+                    true => index.rem_euclid(size as isize) as usize,
+                    false if (0..size as isize).contains(&index) => index as usize,
+                    false => continue,
+                };
+                covered[index / EMPTY_REGION_BLOCK_SIZE] = true;
+                if mirrored {
+                    covered[(size - 1 - index) / EMPTY_REGION_BLOCK_SIZE] = true;
+                }
+            }
+            covered
+        };
+        let is_empty = (0..blocks(h))
+            .map(|block_raw| {
+                (0..blocks(w))
+                    .map(|block_col| {
+                        let raw_span = span(block_raw, radius_y, h);
+                        let col_span = span(block_col, radius_x, w);
+                        // across a flipped seam, the cells of the other axis are mirrored
+                        let raws = covered_blocks(
+                            raw_span,
+                            h,
+                            boundary.wrap_y,
+                            boundary.flip_x && crosses_seam(col_span, w, boundary.wrap_x),
+                        );
+                        let cols = covered_blocks(
+                            col_span,
+                            w,
+                            boundary.wrap_x,
+                            boundary.flip_y && crosses_seam(raw_span, h, boundary.wrap_y),
+                        );
+                        !is_occupied
+                            .iter()
+                            .zip(&raws)
+                            .any(|(is_occupied, &covered)| {
+                                covered
+                                    && is_occupied
+                                        .iter()
+                                        .zip(&cols)
+                                        .any(|(&is_occupied, &covered)| is_occupied && covered)
+                            })
+                    })
+                    .collect()
+            })
+            .collect();
+        Self { is_empty }
+    }
+
+    /// whether the neighbourhood of (raw, col) is all zeros
+    fn is_empty_around(&self, (raw, col): (usize, usize)) -> bool {
+        self.is_empty[raw / EMPTY_REGION_BLOCK_SIZE][col / EMPTY_REGION_BLOCK_SIZE]
+    }
+}
+
+/// next frame of Lenia, wrapping around the edges of the boundary; depends on nothing but its
+/// arguments
+pub fn next_frame(prev: &[Vec<Cell>], params: &SimParams) -> Grid {
+    if prev.first().is_none_or(|cells| cells.is_empty()) {
+        return prev.to_vec();
+    }
+    let (w, h) = (prev[0].len(), prev.len());
+    let total_weight: f64 = params.kernels.iter().map(|spec| spec.weight).sum();
+    let weights = PotentialWeights::new(params, (w, h));
+    let growth_of = |potential: &dyn Fn(usize) -> f64| {
+        let mut growth_mapping = 0.0;
+        for (index, spec) in params.kernels.iter().enumerate() {
+            growth_mapping += spec.weight * spec.growth(potential(index));
+        }
+        growth_mapping / total_weight
+    };
+    // every potential of a cell surrounded by zeros is 0, even the inner over outer ratio
+    let empty_regions = params.skip_empty_regions.then(|| {
+        let radius = params.kernels.iter().fold((0, 0), |(max_x, max_y), spec| {
+            (
+                max_x.max(spec.table.radius.0),
+                max_y.max(spec.table.radius.1),
+            )
+        });
+        EmptyRegions::new(prev, radius, params.boundary)
+    });
+    let empty_growth = growth_of(&|_| 0.0);
+
+    let mut next_frame_cells = prev.to_vec();
+    let mut growths = vec![0.0; w];
+    for (raw, next_raw) in next_frame_cells.iter_mut().enumerate() {
+        for (col, growth) in growths.iter_mut().enumerate() {
+            *growth = match &empty_regions {
+                Some(empty_regions) if empty_regions.is_empty_around((raw, col)) => empty_growth,
+                _ => growth_of(&|index| weights.potential(index, prev, (raw, col))),
+            };
+        }
+        integrate_growth(
+            &prev[raw],
+            &growths,
+            params.delta_t,
+            params.saturation,
+            next_raw,
+        );
+    }
+    next_frame_cells
+}
+
+/// next frame of a Generations automaton, wrapping around the edges of `boundary`
+fn next_generations_frame(
+    cells: &Grid,
+    rule: &GenerationsRule,
+    neighbourhood: Neighbourhood,
+    boundary: Boundary,
+) -> Grid {
+    let (w, h) = (cells[0].len(), cells.len());
+    let offsets: Vec<_> = neighbourhood.offsets().collect();
+    let dead = rule.states - 1;
+    let mut next_frame_cells = cells.clone();
+    for (raw, next_frame_raw) in next_frame_cells.iter_mut().enumerate() {
+        for (col, next_frame_cell) in next_frame_raw.iter_mut().enumerate() {
+            let alive_cells_count = offsets
+                .iter()
+                .filter_map(|&offset| boundary.neighbour((w, h), (raw, col), offset))
+                .filter(|&(raw, col)| cells[raw][col] == 1.0)
+                .count() as u8;
+
+            let next_state = match rule.state(cells[raw][col]) {
+                0 if rule.life.survive.contains(&alive_cells_count) => 0,
+                state if state == dead => {
+                    if rule.life.birth.contains(&alive_cells_count) {
+                        0
+                    } else {
+                        dead
+                    }
+                }
+                // the live cell starts dying, or the dying one gets closer to dead
+                state => state + 1,
+            };
+            *next_frame_cell = rule.value(next_state);
+        }
+    }
+    next_frame_cells
+}
+
+/// randomize a `density` fraction of the cells of `area` (clamped to the grid) according to
+/// `mode`, killing the others
+fn seed_area(
+    cells: &mut Grid,
+    mode: &Mode,
+    rng: &mut fastrand::Rng,
+    ((area_w_min, mut area_w_max), (area_h_min, mut area_h_max)): ((usize, usize), (usize, usize)),
+    density: f32,
+) {
+    let (wcell_count, hcell_count) = (cells[0].len(), cells.len());
+    if area_w_max >= wcell_count {
+        area_w_max = wcell_count - 1;
+    }
+    if area_h_max >= hcell_count {
+        area_h_max = hcell_count - 1;
+    }
+
+    let random_cell: fn(&mut fastrand::Rng) -> Cell = match mode {
+        Mode::Lenia => |rng| rng.f32() as Cell,
+        Mode::GameOfLife | Mode::Generations(_) => |rng| rng.usize(0..=1) as Cell,
+        Mode::Custom(automaton) => {
+            let mut area =
+                vec![vec![0.0; area_w_max + 1 - area_w_min]; area_h_max + 1 - area_h_min];
+            automaton.init(&mut area, rng);
+            for (raw, area_raw) in cells[area_h_min..=area_h_max].iter_mut().zip(area) {
+                raw[area_w_min..=area_w_max].copy_from_slice(&area_raw);
+            }
+            if density < 1.0 {
+                for cell in cells[area_h_min..=area_h_max]
+                    .iter_mut()
+                    .flat_map(|raw| &mut raw[area_w_min..=area_w_max])
+                {
+                    if rng.f32() >= density {
+                        *cell = 0.0;
+                    }
+                }
+            }
+            return;
+        }
+    };
+    for raw in cells.iter_mut().take(area_h_max + 1).skip(area_h_min) {
+        for cell in raw.iter_mut().take(area_w_max + 1).skip(area_w_min) {
+            // a full density draws nothing more, keeping seeded soups as they were
+            *cell = if density >= 1.0 || rng.f32() < density {
+                random_cell(rng)
+            } else {
+                0.0
+            };
+        }
+    }
+}
+
+/// roll `grid` right by `dx` and down by `dy`, in place
+fn rotate_grid<T>(grid: &mut [Vec<T>], dx: usize, dy: usize) {
+    if dx != 0 {
+        for raw in grid.iter_mut() {
+            raw.rotate_right(dx);
+        }
+    }
+    grid.rotate_right(dy);
+}
+
+/// `grid` resized to (w, h), its content offset by (pad_left, pad_top), new cells being zeroed
+fn resized_grid<T: Copy + Default>(
+    grid: &[Vec<T>],
+    (w, h): (usize, usize),
+    (pad_left, pad_top): (isize, isize),
+) -> Vec<Vec<T>> {
+    let mut resized = vec![vec![T::default(); w]; h];
+    for (raw, new_raw) in resized.iter_mut().enumerate() {
+        let old_raw = usize::try_from(raw as isize - pad_top).ok();
+        let Some(old_raw) = old_raw.and_then(|old_raw| grid.get(old_raw)) else {
+            continue;
+        };
+        for (col, cell) in new_raw.iter_mut().enumerate() {
+            let old_col = usize::try_from(col as isize - pad_left).ok();
+            if let Some(&old_cell) = old_col.and_then(|old_col| old_raw.get(old_col)) {
+                *cell = old_cell;
+            }
+        }
+    }
+    resized
+}
+
+/// grid positions under the brush centered on (raw, col) wrapping around the edges, along with
+/// their offset from the center
+fn brush_positions(
+    (raw, col): (usize, usize),
+    shape: BrushShape,
+    radius: usize,
+    (w, h): (usize, usize),
+) -> impl Iterator<Item = ((usize, usize), (isize, isize))> {
+    shape.offsets(radius).map(move |(draw, dcol)| {
+        (
+            (
+                (raw as isize + draw).rem_euclid(h as isize) as usize,
+                (col as isize + dcol).rem_euclid(w as isize) as usize,
+            ),
+            (draw, dcol),
+        )
+    })
+}
+
+/// random ((area_w_min, area_w_max), (area_h_min, area_h_max)), bounds included, of a grid of
+/// the given size
+pub fn generate_spawn_area(
+    wcell_count: usize,
+    hcell_count: usize,
+    rng: &mut fastrand::Rng,
+) -> ((usize, usize), (usize, usize)) {
+    let mut bounds = |cell_count| {
+        let min = rng.usize(0..cell_count);
+        (min, rng.usize(min..cell_count))
+    };
+    (bounds(wcell_count), bounds(hcell_count))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// the HighLife replicator, as (raw, col) live cells
+    const REPLICATOR: [(usize, usize); 12] = [
+        (0, 2),
+        (0, 3),
+        (0, 4),
+        (1, 1),
+        (1, 4),
+        (2, 0),
+        (2, 4),
+        (3, 0),
+        (3, 3),
+        (4, 0),
+        (4, 1),
+        (4, 2),
+    ];
+
+    /// `size`x`size` grid fully seeded from a fixed generator
+    fn seeded_lenia(size: usize, mode: Mode) -> Lenia {
+        Lenia::with_rng(
+            (size, size),
+            Some(((0, size - 1), (0, size - 1))),
+            Some(mode),
+            None,
+            Some(5),
+            fastrand::Rng::with_seed(42),
+        )
+    }
+
+    /// a glider heading down and to the right, as (raw, col) live cells
+    const GLIDER: [(usize, usize); 5] = [(0, 1), (1, 2), (2, 0), (2, 1), (2, 2)];
+
+    /// dead `size`x`size` Game of Life grid with the `live` cells offset by (raw, col)
+    fn life_with(size: usize, live: &[(usize, usize)], at: (usize, usize)) -> Lenia {
+        let mut lenia = seeded_lenia(size, Mode::GameOfLife);
+        lenia.clear();
+        for &(raw, col) in live {
+            lenia.cells[at.0 + raw][at.1 + col] = 1.0;
+            lenia.activate_around(at.0 + raw, at.1 + col);
+        }
+        lenia
+    }
+
+    #[test]
+    fn gol_rule_parses_b_s_notation() {
+        let parse = |rule: &str| rule.parse::<GolRule>();
+        assert_eq!(parse("B3/S23"), Ok(GolRule::default()));
+        let high_life = GolRule {
+            birth: vec![3, 6],
+            survive: vec![2, 3],
+        };
+        assert_eq!(parse("B36/S23"), Ok(high_life.clone()));
+        assert_eq!(parse("b63/s32"), Ok(high_life));
+        assert_eq!(
+            parse("B2/S"),
+            Ok(GolRule {
+                birth: vec![2],
+                survive: vec![],
+            })
+        );
+        for malformed in ["", "B3S23", "S23/B3", "B3/S29", "B3/S2x", "23/3"] {
+            assert!(parse(malformed).is_err(), "{malformed}");
+        }
+    }
+
+    #[test]
+    fn high_life_replicator_copies_itself_in_12_steps() {
+        let mut lenia = life_with(32, &REPLICATOR, (12, 12));
+        lenia.gol_rule = "B36/S23".parse().unwrap();
+        for _ in 0..12 {
+            lenia.compute_next_frame();
+        }
+
+        let mut copies = life_with(32, &REPLICATOR, (10, 10));
+        for &(raw, col) in &REPLICATOR {
+            copies.cells[14 + raw][14 + col] = 1.0;
+        }
+        assert_eq!(lenia.cells, copies.cells);
+
+        // Conway's rule lacks the birth on 6 the replicator relies on
+        let mut conway = life_with(32, &REPLICATOR, (12, 12));
+        for _ in 0..12 {
+            conway.compute_next_frame();
+        }
+        assert_ne!(conway.cells, copies.cells);
+    }
+
+    #[test]
+    fn live_bounds_wrap_around_the_edges_a_structure_straddles() {
+        let mut lenia = life_with(32, &[], (0, 0));
+        assert_eq!(lenia.live_bounds(0.1), None);
+
+        for raw in 14..=17 {
+            for col in 12..=19 {
+                lenia.cells[raw][col] = 0.5;
+            }
+        }
+        // at the threshold isn't above it
+        lenia.cells[20][25] = 0.1;
+        assert_eq!(lenia.live_bounds(0.1), Some(((12, 19), (14, 17))));
+
+        let mut lenia = life_with(32, &[], (0, 0));
+        for (raw, col) in [(31, 30), (31, 1), (0, 31), (0, 0)] {
+            lenia.cells[raw][col] = 0.5;
+        }
+        assert_eq!(lenia.live_bounds(0.1), Some(((30, 1), (31, 0))));
+    }
+
+    #[test]
+    fn centered_resizes_keep_the_center_cell_at_the_center() {
+        let lit_cells = |lenia: &Lenia| {
+            let cells = lenia.cells.iter().enumerate().flat_map(|(raw, cells)| {
+                let lit = cells.iter().enumerate().filter(|(_, &cell)| cell == 1.0);
+                lit.map(move |(col, _)| (raw, col))
+            });
+            cells.collect::<Vec<_>>()
+        };
+        let mut lenia = life_with(33, &[], (0, 0));
+        lenia.cells[16][16] = 1.0;
+        lenia.resize_anchor = ResizeAnchor::Center;
+        for (size, center) in [(65, 32), (17, 8), (33, 16)] {
+            lenia.resize(size, size);
+            assert_eq!(lit_cells(&lenia), vec![(center, center)], "{size}");
+        }
+        // non-square, and back
+        lenia.resize(41, 21);
+        assert_eq!(lit_cells(&lenia), vec![(10, 20)]);
+        lenia.resize(33, 33);
+        assert_eq!(lit_cells(&lenia), vec![(16, 16)]);
+
+        lenia.resize_anchor = ResizeAnchor::TopLeft;
+        lenia.resize(65, 65);
+        assert_eq!(lit_cells(&lenia), vec![(16, 16)]);
+    }
+
+    #[test]
+    fn ages_count_the_steps_alive_and_restart_at_death() {
+        // a block, and a horizontal blinker at (7, 6)
+        let block_and_blinker = [(0, 0), (0, 1), (1, 0), (1, 1), (5, 4), (5, 5), (5, 6)];
+        let mut lenia = life_with(12, &block_and_blinker, (2, 2));
+        for step in 1..=4 {
+            lenia.compute_next_frame();
+            for (raw, col) in [(2, 2), (2, 3), (3, 2), (3, 3)] {
+                assert_eq!(lenia.age[raw][col], step, "block ({raw}, {col})");
+            }
+            // the blinker's center always lives, its ends die every other step
+            assert_eq!(lenia.age[7][7], step);
+            let (vertical_end, horizontal_end) = if step % 2 == 1 { (1, 0) } else { (0, 1) };
+            assert_eq!(lenia.age[6][7], vertical_end, "step {step}");
+            assert_eq!(lenia.age[7][6], horizontal_end, "step {step}");
+            // never alive
+            assert_eq!(lenia.age[0][11], 0);
+        }
+    }
+
+    #[test]
+    fn kernel_core_is_finite_and_non_negative_up_to_the_radius() {
+        for radius in [1, 2, 5, 13, 40] {
+            for peaks in [&[1.0][..], &[0.5, 1.0, 0.25]] {
+                for is_quantized in [false, true] {
+                    for distance in 0..=radius {
+                        let r = distance as f64 / radius as f64;
+                        let weight = kernel_core_function(r, peaks, is_quantized, 4.0);
+                        assert!(weight.is_finite() && weight >= 0.0, "{r} {weight}");
+                    }
+                }
+            }
+        }
+        // the singular ends of the bump
+        assert_eq!(kernel_core_function(0.0, &[1.0], false, 4.0), 0.0);
+        assert_eq!(kernel_core_function(1.0, &[1.0], false, 4.0), 0.0);
+    }
+
+    /// every cell dies
+    struct Extinction;
+
+    impl Automaton for Extinction {
+        fn step(&mut self, _prev: &Grid, next: &mut Grid) {
+            for cell in next.iter_mut().flatten() {
+                *cell = 0.0;
+            }
+        }
+    }
+
+    /// every cell turns into its complement
+    struct Complement;
+
+    impl Automaton for Complement {
+        fn step(&mut self, prev: &Grid, next: &mut Grid) {
+            for (next_cell, &cell) in next.iter_mut().flatten().zip(prev.iter().flatten()) {
+                *next_cell = 1.0 - cell;
+            }
+        }
+    }
+
+    #[test]
+    fn custom_automata_can_be_swapped_between_steps() {
+        let mut lenia = seeded_lenia(16, Mode::Custom(Box::new(Complement)));
+        let seed = lenia.cells.clone();
+        assert!(seed.iter().flatten().any(|&cell| cell > 0.0));
+        lenia.compute_next_frame();
+        let complement: Grid = seed
+            .iter()
+            .map(|cells| cells.iter().map(|&cell| 1.0 - cell).collect())
+            .collect();
+        assert_eq!(lenia.cells, complement);
+
+        lenia.mode = Mode::Custom(Box::new(Extinction));
+        lenia.compute_next_frame();
+        assert!(lenia.cells.iter().flatten().all(|&cell| cell == 0.0));
+    }
+
+    #[test]
+    fn invert_flips_every_cell_but_the_game_of_life_walls() {
+        for mode in [Mode::GameOfLife, Mode::Lenia] {
+            let is_gol = matches!(mode, Mode::GameOfLife);
+            let mut lenia = seeded_lenia(8, mode);
+            lenia.clear();
+            lenia.walls.insert((3, 3));
+            lenia.invert();
+            assert_eq!(lenia.cells[3][3], if is_gol { 0.0 } else { 1.0 });
+            assert_eq!(lenia.cells[3][4], 1.0);
+        }
+    }
+
+    #[test]
+    fn noise_moves_cells_by_the_amplitude_at_most() {
+        let amplitude = 0.2;
+        let mut lenia = seeded_lenia(32, Mode::Lenia);
+        let before = lenia.cells.clone();
+        lenia.add_noise(amplitude);
+        let moves = before.iter().flatten().zip(lenia.cells.iter().flatten());
+        for (&cell, &noisy_cell) in moves {
+            assert!((0.0..=1.0).contains(&noisy_cell), "{noisy_cell}");
+            assert!((noisy_cell - cell).abs() <= amplitude as Cell + 1e-6);
+        }
+        assert_ne!(lenia.cells, before);
+
+        // binary cells flip instead, about an amplitude fraction of them
+        let mut lenia = seeded_lenia(32, Mode::GameOfLife);
+        let before = lenia.cells.clone();
+        lenia.add_noise(amplitude);
+        let flips = before.iter().flatten().zip(lenia.cells.iter().flatten());
+        let mut flip_count = 0;
+        for (&cell, &noisy_cell) in flips {
+            assert!(noisy_cell == 0.0 || noisy_cell == 1.0);
+            if noisy_cell != cell {
+                assert_eq!(noisy_cell, 1.0 - cell);
+                flip_count += 1;
+            }
+        }
+        let expected_flips = amplitude as f64 * 32.0 * 32.0;
+        assert!((flip_count as f64 - expected_flips).abs() < expected_flips / 4.0);
+    }
+
+    #[test]
+    fn only_game_of_life_noise_keeps_the_walls() {
+        for mode in [
+            Mode::GameOfLife,
+            Mode::Generations("B2/S/3".parse().unwrap()),
+        ] {
+            let is_gol = matches!(mode, Mode::GameOfLife);
+            let mut lenia = seeded_lenia(8, mode);
+            lenia.clear();
+            lenia.walls.insert((3, 3));
+            // binary cells all flip at an amplitude of 1
+            lenia.add_noise(1.0);
+            assert_eq!(lenia.cells[3][3], if is_gol { 0.0 } else { 1.0 });
+            assert_eq!(lenia.cells[3][4], 1.0);
+        }
+    }
+
+    #[test]
+    fn elliptical_kernels_reach_their_radius_along_each_axis() {
+        let extreme_offsets = |kernel: &Kernel| {
+            let weights = kernel.weights.iter();
+            weights.fold((0, 0), |(max_draw, max_dcol), &(draw, dcol, _)| {
+                (max_draw.max(draw.abs()), max_dcol.max(dcol.abs()))
+            })
+        };
+        // the core is 0 at the edge of the ellipse, so the farthest weighted neighbour is a cell
+        // closer than the radius along each axis
+        let wide = Kernel::new((6, 3), &[1.0], false, 4.0);
+        assert_eq!(extreme_offsets(&wide), (2, 5));
+        assert!(wide
+            .weights
+            .iter()
+            .any(|&(draw, dcol, _)| (draw, dcol) == (0, 5)));
+        assert!(wide
+            .weights
+            .iter()
+            .any(|&(draw, dcol, _)| (draw, dcol) == (2, 0)));
+        assert!(!wide
+            .weights
+            .iter()
+            .any(|&(draw, dcol, _)| (draw, dcol) == (2, 5)));
+        let tall = Kernel::new((3, 6), &[1.0], false, 4.0);
+        assert_eq!(extreme_offsets(&tall), (5, 2));
+
+        let small_ring = KernelSpec {
+            relative_radius: 0.1,
+            ..KernelSpec::default()
+        };
+        assert_eq!(small_ring.radius((4, 13)), (1, 1));
+        let flat = Kernel::new((0, 4), &[1.0], false, 4.0);
+        assert_eq!(flat.radius, (1, 4));
+        assert!(flat
+            .weights
+            .iter()
+            .all(|&(_, _, weight)| weight.is_finite()));
+        let total: f64 = flat.weights.iter().map(|&(_, _, weight)| weight).sum();
+        assert!((total - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn active_cells_evolve_a_glider_as_a_full_scan_does() {
+        // the glider goes across both wrapping edges and back to where it started, a cell down
+        // and to the right every 4 steps
+        let mut active = life_with(16, &GLIDER, (11, 12));
+        let mut full_scan = life_with(16, &GLIDER, (11, 12));
+        let start = active.cells.clone();
+        for generation in 1..=64 {
+            active.compute_next_frame();
+            full_scan.scan_all_cells = true;
+            full_scan.compute_next_frame();
+            assert_eq!(active.cells, full_scan.cells, "generation {generation}");
+            assert_eq!(active.total_mass(), 5.0, "generation {generation}");
+        }
+        assert_eq!(active.cells, start);
+    }
+
+    #[test]
+    fn active_cells_are_the_changed_cells_and_their_neighbours() {
+        let mut lenia = life_with(16, &[(0, 0), (0, 1), (0, 2)], (7, 6));
+        lenia.compute_next_frame();
+        // the ends died and the cells above and below the center were born
+        let changed_cells = [(7, 6), (7, 8), (6, 7), (8, 7)];
+        let mut expected = AHashSet::new();
+        for (raw, col) in changed_cells {
+            for draw in -1..=1 {
+                for dcol in -1..=1 {
+                    expected.insert(((raw + draw) as usize, (col + dcol) as usize));
+                }
+            }
+        }
+        assert_eq!(lenia.active_cells, expected);
+        // the center never changes but neighbours them all
+        assert!(lenia.active_cells.contains(&(7, 7)));
+        assert!(!lenia.active_cells.contains(&(4, 7)));
+    }
+
+    #[test]
+    fn kernel_alpha_reshapes_the_weights_which_still_sum_to_1() {
+        let mut lenia = seeded_lenia(32, Mode::Lenia);
+        let mut tables = vec![];
+        for alpha in [2.0, 8.0] {
+            lenia.kernel_alpha = alpha;
+            lenia.compute_next_lenia_frame();
+            let table = lenia.kernels[0].table.clone();
+            assert_eq!(table.alpha, alpha);
+            let total_weight: f64 = table.weights.iter().map(|&(_, _, weight)| weight).sum();
+            assert!((total_weight - 1.0).abs() < 1e-9, "{alpha}: {total_weight}");
+            tables.push(table);
+        }
+        let offsets = |table: &Kernel| {
+            let offsets = table.weights.iter().map(|&(draw, dcol, _)| (draw, dcol));
+            offsets.collect::<Vec<_>>()
+        };
+        assert_eq!(offsets(&tables[0]), offsets(&tables[1]));
+        assert_ne!(tables[0].weights, tables[1].weights);
+    }
+
+    #[test]
+    fn bilinear_samples_blend_the_surrounding_cells() {
+        let mut lenia = seeded_lenia(8, Mode::Lenia);
+        lenia.clear();
+        // a 0/1 pair along x, then along y
+        lenia.cells[2][4] = 1.0;
+        assert_eq!(lenia.sample_bilinear(4.0, 2.0), 1.0);
+        assert_eq!(lenia.sample_bilinear(3.5, 2.0), 0.5);
+        assert_eq!(lenia.sample_bilinear(3.25, 2.0), 0.25);
+        assert_eq!(lenia.sample_bilinear(4.75, 2.0), 0.25);
+        assert_eq!(lenia.sample_bilinear(4.0, 1.5), 0.5);
+        assert_eq!(lenia.sample_bilinear(4.0, 2.25), 0.75);
+        // a quarter of the way along both axes
+        assert_eq!(lenia.sample_bilinear(3.75, 1.75), 0.75 * 0.75);
+
+        // across the wrapped edge
+        lenia.clear();
+        lenia.cells[0][0] = 1.0;
+        assert_eq!(lenia.sample_bilinear(7.5, 0.0), 0.5);
+        assert_eq!(lenia.sample_bilinear(-0.25, 0.0), 0.75);
+    }
+
+    #[test]
+    fn diff_reports_the_cells_differing_beyond_epsilon() {
+        let mut lenia = seeded_lenia(4, Mode::Lenia);
+        let mut other = seeded_lenia(4, Mode::Lenia);
+        lenia.cells = vec![vec![0.5; 4]; 4];
+        other.cells = lenia.cells.clone();
+        other.cells[0][3] = 1.0;
+        other.cells[2][1] = 0.25;
+        // within epsilon
+        other.cells[3][3] = 0.5625;
+
+        let diff = lenia.diff(&other, 0.125).unwrap();
+        assert_eq!(diff.differing_cells, vec![(0, 3), (2, 1)]);
+        assert_eq!(diff.max_abs_difference, 0.5);
+        assert_eq!(diff.mean_abs_difference, (0.5 + 0.25 + 0.0625) / 16.0);
+        assert_eq!(
+            lenia.diff(&lenia, 0.0).unwrap(),
+            GridDiff::default(),
+            "a grid is its own match"
+        );
+
+        let signed_diff = other.signed_diff(&lenia.cells).unwrap();
+        assert_eq!(signed_diff[0][3], 0.5);
+        assert_eq!(signed_diff[2][1], -0.25);
+        assert_eq!(signed_diff[1][1], 0.0);
+
+        let smaller = seeded_lenia(3, Mode::Lenia);
+        assert_eq!(
+            lenia.diff(&smaller, 0.0),
+            Err("cannot compare a 4x4 grid with a 3x3 one".to_string())
+        );
+        assert_eq!(lenia.signed_diff(&smaller.cells), None);
+    }
+
+    #[test]
+    fn brians_brain_cells_die_over_two_steps_and_give_birth_around() {
+        let rule: GenerationsRule = "B2/S/3".parse().unwrap();
+        let mut cells = vec![vec![0.0; 8]; 8];
+        cells[3][3] = 1.0;
+        cells[3][4] = 1.0;
+        // alive #, dying +, dead .
+        let picture = |cells: &Grid| {
+            let states = cells.iter().map(|cells| {
+                let states = cells.iter().map(|&cell| match rule.state(cell) {
+                    0 => '#',
+                    1 => '+',
+                    _ => '.',
+                });
+                states.collect::<String>()
+            });
+            states.collect::<Vec<_>>()
+        };
+        let step = |cells: &Grid| {
+            next_generations_frame(cells, &rule, Neighbourhood::Moore, Boundary::default())
+        };
+
+        let cells = step(&cells);
+        assert_eq!(
+            picture(&cells),
+            [
+                "........", "........", "...##...", "...++...", "...##...", "........", "........",
+                "........",
+            ]
+        );
+        assert_eq!(cells[3][3], 0.5);
+
+        let cells = step(&cells);
+        assert_eq!(
+            picture(&cells),
+            [
+                "........", "...##...", "...++...", "..#..#..", "...++...", "...##...", "........",
+                "........",
+            ]
+        );
+    }
+
+    #[cfg(feature = "simd")]
+    #[test]
+    fn simd_integration_matches_the_scalar_one() {
+        let mut rng = fastrand::Rng::with_seed(7);
+        // full lanes and every remainder, growths pushing some cells out of [0, 1]
+        for len in 0..=11 {
+            let cells: Vec<Cell> = (0..len).map(|_| rng.f64() as Cell).collect();
+            let growths: Vec<f64> = (0..len).map(|_| 4.0 * rng.f64() - 2.0).collect();
+            for delta_t in [0.01, 0.1, 1.0] {
+                let mut next = vec![0.0; len];
+                integrate_growth_clamped(&cells, &growths, delta_t, &mut next);
+                for ((&next, &cell), &growth) in next.iter().zip(&cells).zip(&growths) {
+                    let scalar = (cell as f64 + delta_t * growth).clamp(0.0, 1.0) as Cell;
+                    assert!((next - scalar).abs() <= Cell::EPSILON, "{next} {scalar}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn next_frame_keeps_its_invariants_on_random_grids() {
+        let mut rng = fastrand::Rng::with_seed(11);
+        for _ in 0..20 {
+            // odd sizes, for the simd remainders too
+            let (w, h) = (rng.usize(2..24), rng.usize(2..24));
+            let mut lenia = Lenia::with_rng(
+                (w, h),
+                Some(((0, w - 1), (0, h - 1))),
+                Some(Mode::Lenia),
+                Some(0.05 + 0.95 * rng.f64()),
+                Some(rng.usize(1..8)),
+                fastrand::Rng::with_seed(rng.u64(..)),
+            );
+            for cell in lenia.cells.iter_mut().flatten() {
+                *cell = if rng.bool() { rng.f64() as Cell } else { 0.0 };
+            }
+            // mu over 3 sigma: a zero potential shrinks the cells
+            let sigma = 0.01 + 0.1 * rng.f64();
+            lenia.kernels[0].sigma = sigma;
+            lenia.kernels[0].mu = 3.0 * sigma + 0.3 * rng.f64();
+            let cells = lenia.cells.clone();
+            let delta_t = lenia.delta_t;
+            let params = lenia.sim_params();
+            let spec = &params.kernels[0];
+
+            let next = next_frame(&cells, &params);
+            assert!(next
+                .iter()
+                .flatten()
+                .all(|&cell| (0.0..=1.0).contains(&cell)));
+            assert_eq!(next_frame(&cells, &params), next);
+            let zeros = vec![vec![0.0; w]; h];
+            assert_eq!(next_frame(&zeros, &params), zeros);
+
+            // cell by cell, from the potential of each
+            let weights = spec.table.unique_weights((w, h));
+            for raw in 0..h {
+                for col in 0..w {
+                    let mut potential = 0.0;
+                    for &(draw, dcol, weight) in weights.iter() {
+                        let xpos = (col as isize + dcol).rem_euclid(w as isize) as usize;
+                        let ypos = (raw as isize + draw).rem_euclid(h as isize) as usize;
+                        potential += cells[ypos][xpos] as f64 * weight;
+                    }
+                    let expected =
+                        (cells[raw][col] as f64 + delta_t * spec.growth(potential)).clamp(0.0, 1.0);
+                    let next = next[raw][col] as f64;
+                    assert!((next - expected).abs() < 1e-6, "{next} {expected}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn undo_step_restores_the_cells_before_the_last_step_once() {
+        let mut lenia = seeded_lenia(32, Mode::Lenia);
+        let before_step = lenia.cells.clone();
+        lenia.compute_next_frame();
+        assert_ne!(lenia.cells, before_step);
+
+        assert!(lenia.undo_step());
+        assert_eq!(lenia.cells, before_step);
+        assert_eq!(lenia.generation, 0);
+        // a single level: nothing is left to undo
+        assert!(!lenia.undo_step());
+        assert_eq!(lenia.cells, before_step);
+        assert_eq!(lenia.generation, 0);
+    }
+
+    #[test]
+    fn unique_weights_count_each_wrapped_neighbour_once() {
+        let kernel = Kernel::new((5, 5), &[1.0], false, 4.0);
+        let period = (6, 4);
+        let wrapped = |draw: isize, dcol: isize| {
+            (
+                draw.rem_euclid(period.1 as isize),
+                dcol.rem_euclid(period.0 as isize),
+            )
+        };
+        let unique = kernel.unique_weights(period);
+
+        let mut cells: Vec<_> = unique
+            .iter()
+            .map(|&(draw, dcol, _)| wrapped(draw, dcol))
+            .collect();
+        cells.sort_unstable();
+        cells.dedup();
+        assert_eq!(cells.len(), unique.len());
+        assert!(!cells.contains(&(0, 0)));
+        // every neighbour the kernel reaches, apart from the center itself
+        let mut reached: Vec<_> = kernel
+            .weights
+            .iter()
+            .map(|&(draw, dcol, _)| wrapped(draw, dcol))
+            .filter(|&cell| cell != (0, 0))
+            .collect();
+        reached.sort_unstable();
+        reached.dedup();
+        assert_eq!(cells, reached);
+
+        let total_weight: f64 = unique.iter().map(|&(_, _, weight)| weight).sum();
+        assert!((total_weight - 1.0).abs() < 1e-12, "{total_weight}");
+    }
+
+    #[test]
+    fn seed_empty_only_draws_the_cells_below_the_threshold() {
+        let mut lenia = seeded_lenia(16, Mode::Lenia);
+        lenia.clear();
+        lenia.cells[2][3] = 0.5;
+        lenia.cells[4][5] = 0.9;
+        lenia.cells[6][7] = 0.49;
+        lenia.seed_empty(0.5, 1.0);
+        assert_eq!((lenia.cells[2][3], lenia.cells[4][5]), (0.5, 0.9));
+        assert_ne!(lenia.cells[6][7], 0.49);
+        let seeded_cells = lenia.cells.iter().flatten().filter(|&&cell| cell > 0.0);
+        assert!(seeded_cells.count() > 16 * 16 - 8);
+
+        // walls only freeze Game of Life cells
+        lenia.clear();
+        lenia.walls.insert((6, 7));
+        lenia.cells[6][7] = 0.25;
+        lenia.seed_empty(0.5, 1.0);
+        assert_ne!(lenia.cells[6][7], 0.25);
+
+        // Game of Life keeps its walls, dead or alive
+        let mut lenia = seeded_lenia(16, Mode::GameOfLife);
+        lenia.clear();
+        lenia.cells[2][3] = 1.0;
+        lenia.walls.extend([(2, 3), (8, 8)]);
+        for _ in 0..8 {
+            lenia.seed_empty(0.5, 1.0);
+            assert_eq!((lenia.cells[2][3], lenia.cells[8][8]), (1.0, 0.0));
+        }
+        let live_cells = lenia.cells.iter().flatten().filter(|&&cell| cell == 1.0);
+        assert!(live_cells.count() > 16 * 16 / 2);
+    }
+
+    #[test]
+    fn kernels_wider_than_the_grid_are_clamped() {
+        let mut lenia = seeded_lenia(20, Mode::Lenia);
+        lenia.kernel_radius = (13, 4);
+        assert!(lenia.clamp_kernel_radius());
+        assert_eq!(lenia.kernel_radius, (9, 4));
+        // already fits
+        assert!(!lenia.clamp_kernel_radius());
+        assert_eq!(lenia.kernel_radius, (9, 4));
+
+        // the narrowest side bounds both axes
+        let mut lenia = Lenia::builder()
+            .dimensions(40, 8)
+            .kernel_radius(13)
+            .seed(1)
+            .build()
+            .unwrap();
+        assert!(lenia.clamp_kernel_radius());
+        assert_eq!(lenia.kernel_radius, (3, 3));
+    }
+
+    #[test]
+    fn kernel_stack_growth_is_the_weighted_average_of_its_pairs() {
+        let pairs = [
+            KernelSpec {
+                mu: 0.25,
+                sigma: 0.05,
+                weight: 1.0,
+                ..KernelSpec::default()
+            },
+            KernelSpec {
+                relative_radius: 0.5,
+                peaks: vec![0.5, 1.0],
+                mu: 0.35,
+                sigma: 0.1,
+                weight: 3.0,
+                ..KernelSpec::default()
+            },
+        ];
+        let value = 0.3;
+        // a uniform field: every kernel averages it to its value
+        let uniform = |kernels: &[KernelSpec]| {
+            let mut lenia = seeded_lenia(32, Mode::Lenia);
+            for cell in lenia.cells.iter_mut().flatten() {
+                *cell = value;
+            }
+            lenia.delta_t = 0.1;
+            lenia.kernels = kernels.to_vec();
+            lenia
+        };
+        let mut stack = uniform(&pairs);
+        for spec in stack.sim_params().kernels {
+            let weights = spec.table.unique_weights((32, 32));
+            let potential: f64 =
+                weights.iter().map(|&(_, _, weight)| weight).sum::<f64>() * value as f64;
+            assert!((potential - value as f64).abs() < 1e-6, "{potential}");
+        }
+
+        // the growth of a step is its change over delta_t
+        let growth = |mut lenia: Lenia| {
+            lenia.compute_next_frame();
+            (lenia.cells[7][19] - value) as f64 / 0.1
+        };
+        let expected = (growth(uniform(&pairs[..1])) + 3.0 * growth(uniform(&pairs[1..]))) / 4.0;
+        let expected_exactly =
+            (pairs[0].growth(value as f64) + 3.0 * pairs[1].growth(value as f64)) / 4.0;
+        let combined = growth(stack);
+        assert!((combined - expected).abs() < 1e-5, "{combined} {expected}");
+        assert!(
+            (combined - expected_exactly).abs() < 1e-5,
+            "{combined} {expected_exactly}"
+        );
+    }
+
+    #[test]
+    fn identical_seeds_draw_identical_spawn_areas() {
+        let areas = |seed| {
+            let mut rng = fastrand::Rng::with_seed(seed);
+            let areas = (0..20).map(|_| generate_spawn_area(64, 48, &mut rng));
+            areas.collect::<Vec<_>>()
+        };
+        assert_eq!(areas(7), areas(7));
+        assert_ne!(areas(7), areas(8));
+        for ((w_min, w_max), (h_min, h_max)) in areas(7) {
+            assert!(w_min <= w_max && w_max < 64);
+            assert!(h_min <= h_max && h_max < 48);
+        }
+
+        // and so do the instances seeded with them, which seed a random area
+        let soup = |seed| {
+            let rng = fastrand::Rng::with_seed(seed);
+            Lenia::with_rng((32, 32), None, None, None, None, rng).cells
+        };
+        assert_eq!(soup(3), soup(3));
+        assert_ne!(soup(3), soup(4));
+    }
+
+    #[test]
+    fn saturations_map_an_overflowing_value() {
+        for (saturation, expected) in [
+            (Saturation::Clamp, 1.0),
+            (Saturation::Tanh, 1.5f64.tanh()),
+            (Saturation::Wrap, 0.5),
+        ] {
+            assert_eq!(saturation.apply(1.5), expected, "{saturation:?}");
+            // 1 grown by 0.5 in a step
+            let mut next = [0.0];
+            integrate_growth(&[1.0], &[0.5], 1.0, saturation, &mut next);
+            assert!((next[0] as f64 - expected).abs() < 1e-6, "{saturation:?}");
+        }
+        assert_eq!(Saturation::Clamp.apply(-0.25), 0.0);
+        assert_eq!(Saturation::Tanh.apply(-0.25), 0.0);
+        assert_eq!(Saturation::Wrap.apply(-0.25), 0.75);
+    }
+
+    #[test]
+    fn inner_outer_ratio_is_1_over_a_uniform_field() {
+        let mut lenia = seeded_lenia(32, Mode::Lenia);
+        lenia.potential = Potential::InnerOuterRatio {
+            inner_fraction: 0.4,
+        };
+        lenia.kernel_radius = (9, 6);
+        for cell in lenia.cells.iter_mut().flatten() {
+            *cell = 0.3;
+        }
+        let radius = lenia.sim_params().kernels[0].table.radius;
+        let [inner, outer] = inner_outer_weights(radius, 0.4);
+        for raw in (0..32).step_by(5) {
+            for col in (0..32).step_by(7) {
+                let ratio = weighted_sum(&lenia.cells, &inner, (raw, col), lenia.boundary)
+                    / weighted_sum(&lenia.cells, &outer, (raw, col), lenia.boundary);
+                assert!((ratio - 1.0).abs() < 1e-6, "({raw}, {col}): {ratio}");
+            }
+        }
+        // every cell grows the same, so the field stays uniform
+        lenia.compute_next_frame();
+        let first_cell = lenia.cells[0][0];
+        assert!(lenia.cells.iter().flatten().all(|&cell| cell == first_cell));
+    }
+
+    #[test]
+    fn cylinders_wrap_horizontally_only() {
+        // a blinker across the left and right edges, then one across the top and bottom ones
+        let blinker_across = |topology: Topology, cells: [(usize, usize); 3]| {
+            let mut lenia = seeded_lenia(16, Mode::GameOfLife);
+            lenia.clear();
+            lenia.boundary = topology.boundary();
+            for (raw, col) in cells {
+                lenia.cells[raw][col] = 1.0;
+            }
+            lenia.scan_all_cells = true;
+            lenia.compute_next_frame();
+            let mut live_cells = Vec::new();
+            for (raw, cells) in lenia.cells.iter().enumerate() {
+                for (col, &cell) in cells.iter().enumerate() {
+                    if cell > 0.0 {
+                        live_cells.push((raw, col));
+                    }
+                }
+            }
+            live_cells
+        };
+        let horizontal = [(8, 15), (8, 0), (8, 1)];
+        let vertical = [(15, 8), (0, 8), (1, 8)];
+
+        // turned vertical around its center on the seam
+        let turned = vec![(7, 0), (8, 0), (9, 0)];
+        assert_eq!(blinker_across(Topology::Cylinder, horizontal), turned);
+        assert_eq!(blinker_across(Topology::Torus, horizontal), turned);
+        assert!(blinker_across(Topology::Plane, horizontal).is_empty());
+
+        assert!(blinker_across(Topology::Cylinder, vertical).is_empty());
+        assert_eq!(
+            blinker_across(Topology::Torus, vertical),
+            [(0, 7), (0, 8), (0, 9)]
+        );
+    }
+
+    #[test]
+    fn set_mode_binarizes_into_game_of_life_and_restores_the_lenia_defaults() {
+        let mut lenia = seeded_lenia(16, Mode::Lenia);
+        lenia.cells[0][..4].copy_from_slice(&[0.0, 0.49, 0.5, 0.9]);
+        lenia.delta_t = 0.1;
+        lenia.kernel_radius = (4, 4);
+        lenia.target_mass = Some(10.0);
+        let kernels = lenia.kernels.clone();
+
+        lenia.set_mode(Mode::GameOfLife);
+        assert_eq!(lenia.cells[0][..4], [0.0, 0.0, 1.0, 1.0]);
+        assert!(lenia
+            .cells
+            .iter()
+            .flatten()
+            .all(|&cell| cell == 0.0 || cell == 1.0));
+        assert_eq!(lenia.target_mass, None);
+
+        let binary_cells = lenia.cells.clone();
+        lenia.set_mode(Mode::Lenia);
+        assert_eq!(lenia.cells, binary_cells);
+        assert_eq!(lenia.delta_t, DEFAULT_DELTA_T);
+        assert_eq!(
+            lenia.kernel_radius,
+            (DEFAULT_KERNEL_RADIUS, DEFAULT_KERNEL_RADIUS)
+        );
+        assert_eq!(lenia.kernels, kernels);
+    }
+
+    #[test]
+    fn kernel_self_check_accepts_built_kernels_and_rejects_broken_ones() {
+        for (radius, peaks, is_quantized) in [
+            ((13, 13), &[1.0][..], false),
+            ((1, 1), &[1.0], false),
+            ((9, 5), &[0.5, 1.0, 0.3], false),
+            ((13, 13), &[1.0, 0.5], true),
+        ] {
+            let kernel = Kernel::new(radius, peaks, is_quantized, 4.0);
+            assert_eq!(kernel.self_check(), Ok(()), "{radius:?} {peaks:?}");
+        }
+
+        let kernel = Kernel::new((5, 5), &[1.0], false, 4.0);
+        let with_weights = |weights: OffsetWeights| Kernel {
+            weights: Arc::new(weights),
+            ..kernel.clone()
+        };
+        assert!(with_weights(vec![]).self_check().is_err());
+        let zero_weights = kernel
+            .weights
+            .iter()
+            .map(|&(draw, dcol, _)| (draw, dcol, 0.0));
+        assert!(with_weights(zero_weights.collect()).self_check().is_err());
+        // symmetric, but summing to 2
+        let doubled = kernel
+            .weights
+            .iter()
+            .map(|&(draw, dcol, weight)| (draw, dcol, 2.0 * weight));
+        assert!(with_weights(doubled.collect()).self_check().is_err());
+        // one weight moved to another neighbour: still summing to 1, but lopsided
+        let mut lopsided = (*kernel.weights).clone();
+        let (_, _, weight) = lopsided.pop().unwrap();
+        lopsided[0].2 += weight;
+        assert!(with_weights(lopsided).self_check().is_err());
+
+        // custom tables only need to sum to 1
+        let custom = Kernel {
+            is_custom: true,
+            ..with_weights(vec![(0, 1, 1.0)])
+        };
+        assert_eq!(custom.self_check(), Ok(()));
+    }
+
+    #[test]
+    fn potentials_at_leaves_the_instance_as_it_is() {
+        let mut lenia = seeded_lenia(16, Mode::Lenia);
+        lenia.enforce_min_grid = true;
+        lenia.kernel_radius = (13, 13);
+        lenia.kernel_alpha = 2.0;
+        let tables = |lenia: &Lenia| {
+            let tables = lenia.kernels.iter().map(|spec| spec.table.clone());
+            tables.collect::<Vec<_>>()
+        };
+        let stale_tables = tables(&lenia);
+        let potentials = lenia.potentials_at((3, 4));
+        assert_eq!(lenia.kernel_radius, (13, 13));
+        assert_eq!(tables(&lenia), stale_tables);
+
+        // what the next step computes, with the clamped radius
+        lenia.sim_params();
+        assert_eq!(lenia.kernel_radius, (7, 7));
+        assert_eq!(lenia.potentials_at((3, 4)), potentials);
+    }
+
+    #[test]
+    fn symmetry_images_copy_the_same_source_cell() {
+        let (w, h) = (7, 6);
+        for symmetry in [
+            Symmetry::MirrorX,
+            Symmetry::MirrorY,
+            Symmetry::Point,
+            Symmetry::Quadrants,
+        ] {
+            for raw in 0..h {
+                for col in 0..w {
+                    let images = symmetry.images((raw, col), (w, h));
+                    assert!(images.contains(&(raw, col)));
+                    let source = symmetry.source((raw, col), (w, h));
+                    for image in images {
+                        assert_eq!(symmetry.source(image, (w, h)), source, "{symmetry:?}");
+                    }
+                }
+            }
+        }
+        assert_eq!(
+            Symmetry::Quadrants.images((1, 3), (w, h)),
+            vec![(1, 3), (4, 3)],
+            "the middle col is its own mirror"
+        );
+    }
+
+    #[test]
+    fn loaded_states_resume_the_run_random_draws_included() {
+        let mut original = seeded_lenia(24, Mode::Lenia);
+        original.run(3);
+        original.add_noise(0.1);
+        let path = std::env::temp_dir().join(format!("lenia-state-{}.toml", std::process::id()));
+        original.save_state(&path).unwrap();
+        let mut resumed = Lenia {
+            rng: fastrand::Rng::with_seed(7),
+            ..seeded_lenia(16, Mode::Lenia)
+        };
+        let loaded = resumed.load_state(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(loaded.is_ok());
+        assert_eq!(resumed.generation, 3);
+        assert_eq!(resumed.cells, original.cells);
+        // the next reseed draws the same values
+        original.seed_area(((2, 20), (4, 12)), 0.5);
+        resumed.seed_area(((2, 20), (4, 12)), 0.5);
+        assert_eq!(resumed.cells, original.cells);
+        original.compute_next_frame();
+        resumed.compute_next_frame();
+        assert_eq!(resumed.cells, original.cells);
+    }
+
+    #[test]
+    fn seed_blobs_scatter_that_many_live_regions() {
+        let mut lenia = Lenia {
+            rng: fastrand::Rng::with_seed(3),
+            ..seeded_lenia(64, Mode::Lenia)
+        };
+        // with this seed no two of the areas overlap nor touch
+        lenia.seed_blobs(3);
+        // flood fill the 8-connected live regions, without wrapping like the spawn areas
+        let mut seen = vec![vec![false; 64]; 64];
+        let mut regions = 0;
+        for start in (0..64).flat_map(|raw| (0..64).map(move |col| (raw, col))) {
+            if seen[start.0][start.1] || lenia.cells[start.0][start.1] == 0.0 {
+                continue;
+            }
+            regions += 1;
+            let mut stack = vec![start];
+            seen[start.0][start.1] = true;
+            while let Some((raw, col)) = stack.pop() {
+                let neighbours = (raw.saturating_sub(1)..=(raw + 1).min(63)).flat_map(|nraw| {
+                    (col.saturating_sub(1)..=(col + 1).min(63)).map(move |ncol| (nraw, ncol))
+                });
+                for (nraw, ncol) in neighbours {
+                    if !seen[nraw][ncol] && lenia.cells[nraw][ncol] != 0.0 {
+                        seen[nraw][ncol] = true;
+                        stack.push((nraw, ncol));
+                    }
+                }
+            }
+        }
+        assert_eq!(regions, 3);
+    }
+
+    #[test]
+    fn kernel_tables_are_shared_until_the_kernel_changes() {
+        let mut lenia = seeded_lenia(32, Mode::Lenia);
+        lenia.compute_next_frame();
+        let table = Arc::clone(&lenia.kernels[0].table.weights);
+        lenia.compute_next_frame();
+        assert!(Arc::ptr_eq(&table, &lenia.kernels[0].table.weights));
+
+        // clones and wide enough tori read the same table
+        let kernel = lenia.kernels[0].table.clone();
+        assert!(Arc::ptr_eq(&table, &kernel.weights));
+        assert!(Arc::strong_count(&table) > 2);
+        assert!(Arc::ptr_eq(&table, &kernel.unique_weights((32, 32))));
+
+        lenia.kernel_radius = (7, 7);
+        lenia.compute_next_frame();
+        assert!(!Arc::ptr_eq(&table, &lenia.kernels[0].table.weights));
+        assert_eq!(lenia.kernels[0].table.radius, (7, 7));
+    }
+
+    #[test]
+    fn target_mass_holds_the_total_mass_every_step() {
+        let mut lenia = seeded_lenia(48, Mode::Lenia);
+        lenia.clear();
+        lenia.seed_area(((10, 30), (10, 30)), 0.5);
+        lenia.target_mass = Some(60.0);
+        for generation in 1..=10 {
+            lenia.compute_next_frame();
+            let mass = lenia.total_mass();
+            assert!(
+                (mass - 60.0).abs() < 1e-3,
+                "generation {generation}: {mass}"
+            );
+        }
+    }
+
+    #[test]
+    fn scale_mass_to_passes_the_share_of_full_cells_on() {
+        let mut lenia = seeded_lenia(4, Mode::Lenia);
+        lenia.clear();
+        lenia.cells[0][0] = 0.9;
+        lenia.cells[1][2] = 0.1;
+        lenia.scale_mass_to(1.5);
+        assert_eq!(lenia.cells[0][0], 1.0);
+        assert!((lenia.cells[1][2] - 0.5).abs() < 1e-6);
+        assert!((lenia.total_mass() - 1.5).abs() < 1e-6);
+        lenia.scale_mass_to(0.75);
+        assert!((lenia.cells[0][0] - 0.5).abs() < 1e-6);
+        // every living cell full: as close as it gets, the dead ones staying dead
+        lenia.scale_mass_to(10.0);
+        assert_eq!(lenia.total_mass(), 2.0);
+    }
+
+    #[test]
+    fn step_kernel_radius_stays_within_the_grid() {
+        for size in [2, 5, 32] {
+            let mut lenia = seeded_lenia(size, Mode::Lenia);
+            let max_radius = (size / 2 - 1).max(1);
+            assert_eq!(lenia.max_kernel_radius(), max_radius);
+            for (steps, expected) in [
+                (-100, 1),
+                (1, 2.min(max_radius)),
+                (100, max_radius),
+                (-1, (max_radius - 1).max(1)),
+            ] {
+                let radius = lenia.step_kernel_radius(steps);
+                assert_eq!(radius, (expected, expected), "{size} {steps}");
+                let table = &lenia.kernels[0].table;
+                assert_eq!(table.radius, radius);
+                assert_eq!(table.self_check(), Ok(()), "{size} {steps}");
+                lenia.compute_next_frame();
+            }
+        }
+    }
+
+    #[test]
+    fn gliders_cross_wrapped_seams_as_they_are_and_flipped_ones_mirrored() {
+        let glider_at = |topology: Topology, generations| {
+            let mut lenia = life_with(16, &GLIDER, (2, 10));
+            lenia.boundary = topology.boundary();
+            lenia.run(generations);
+            lenia.cells
+        };
+        let mirrored = |cells: &Grid| cells.iter().rev().cloned().collect::<Grid>();
+
+        // the glider heads down and to the right, a cell every 4 steps: not yet at the right edge
+        let torus = glider_at(Topology::Torus, 8);
+        assert_eq!(glider_at(Topology::KleinBottle, 8), torus);
+
+        // fully across the right edge, back on the left, not yet at the bottom
+        let torus = glider_at(Topology::Torus, 36);
+        let mut plane = life_with(16, &GLIDER, (2, 10));
+        plane.boundary = Topology::Plane.boundary();
+        plane.run(36);
+        assert_ne!(plane.cells, torus);
+        assert_eq!(torus.iter().flatten().sum::<Cell>(), 5.0);
+        assert!(torus
+            .iter()
+            .all(|cells| cells[13..].iter().all(|&cell| cell == 0.0)));
+        // upside down across the flipped seam, heading up instead
+        let klein_bottle = glider_at(Topology::KleinBottle, 36);
+        assert_eq!(klein_bottle, mirrored(&torus));
+        assert_ne!(klein_bottle, torus);
+    }
+
+    #[test]
+    fn active_cells_empty_once_the_grid_settles() {
+        // three cells of a block, completed on the first step, then still
+        let mut lenia = life_with(16, &[(0, 0), (0, 1), (1, 0)], (6, 6));
+        lenia.compute_next_frame();
+        assert!(!lenia.active_cells.is_empty());
+        lenia.compute_next_frame();
+        assert!(lenia.active_cells.is_empty());
+        assert_eq!(lenia.total_mass(), 4.0);
+    }
+
+    #[test]
+    fn shrinking_the_grid_to_nothing_keeps_the_smallest_grid() {
+        for is_lenia in [true, false] {
+            for (w, h) in [(0, 0), (1, 1), (0, 5)] {
+                let mode = if is_lenia {
+                    Mode::Lenia
+                } else {
+                    Mode::GameOfLife
+                };
+                let mut lenia = seeded_lenia(16, mode);
+                lenia.resize(w, h);
+                assert_eq!(lenia.cells.len(), h.max(MIN_GRID_SIZE));
+                assert!(lenia
+                    .cells
+                    .iter()
+                    .all(|cells| cells.len() == w.max(MIN_GRID_SIZE)));
+                lenia.run(2);
+            }
+        }
+    }
+
+    #[test]
+    fn skipping_empty_regions_gives_the_frames_of_the_full_convolution() {
+        for topology in [Topology::Torus, Topology::KleinBottle, Topology::Plane] {
+            let mut skipping = seeded_lenia(64, Mode::Lenia);
+            skipping.clear();
+            // a patch near the edges, amid empty space
+            skipping.seed_area(((2, 17), (50, 62)), 0.5);
+            skipping.boundary = topology.boundary();
+            let mut full = Lenia {
+                cells: skipping.cells.clone(),
+                skip_empty_regions: false,
+                ..seeded_lenia(64, Mode::Lenia)
+            };
+            full.boundary = skipping.boundary;
+            for _ in 0..5 {
+                skipping.compute_next_frame();
+                full.compute_next_frame();
+                assert_eq!(skipping.cells, full.cells, "{topology}");
+            }
+            assert!(skipping.total_mass() > 0.0, "{topology}");
+        }
+    }
+
+    #[test]
+    fn von_neumann_counts_the_4_orthogonal_neighbours_only() {
+        let mut offsets: Vec<_> = Neighbourhood::VonNeumann.offsets().collect();
+        offsets.sort_unstable();
+        assert_eq!(offsets, [(-1, 0), (0, -1), (0, 1), (1, 0)]);
+        assert_eq!(Neighbourhood::Moore.offsets().count(), 8);
+        assert_eq!(Neighbourhood::MooreRange(2).offsets().count(), 24);
+
+        // B1/S: a dead cell comes to life next to exactly one live cell, the live ones die
+        let single_cell = |neighbourhood| {
+            let mut lenia = life_with(9, &[(0, 0)], (4, 4));
+            lenia.gol_rule = "B1/S".parse().unwrap();
+            lenia.neighbourhood = neighbourhood;
+            lenia.compute_next_frame();
+            let mut live = lenia.live_cells();
+            live.sort_unstable();
+            live
+        };
+        assert_eq!(
+            single_cell(Neighbourhood::VonNeumann),
+            [(3, 4), (4, 3), (4, 5), (5, 4)]
+        );
+        assert_eq!(single_cell(Neighbourhood::Moore).len(), 8);
+
+        // the dead center has 4 diagonal and 2 orthogonal live neighbours: born on 2 with von
+        // Neumann only, on 6 with Moore only
+        let center_with = |rule: &str, neighbourhood| {
+            let mut lenia = life_with(9, &[(0, 0), (0, 2), (1, 0), (1, 2), (2, 0), (2, 2)], (3, 3));
+            lenia.gol_rule = rule.parse().unwrap();
+            lenia.neighbourhood = neighbourhood;
+            lenia.compute_next_frame();
+            lenia.cells[4][4]
+        };
+        assert_eq!(center_with("B2/S", Neighbourhood::VonNeumann), 1.0);
+        assert_eq!(center_with("B2/S", Neighbourhood::Moore), 0.0);
+        assert_eq!(center_with("B6/S", Neighbourhood::VonNeumann), 0.0);
+        assert_eq!(center_with("B6/S", Neighbourhood::Moore), 1.0);
+    }
+}
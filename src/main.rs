@@ -1,255 +1,679 @@
-use ahash::AHashSet;
+// casts between `Cell` and f32 or f64 are no-ops with one of the two cell types
+#![allow(clippy::unnecessary_cast)]
+
+mod command;
+mod events;
+mod keymap;
+mod replay;
+mod watch;
+
+use command::{Command, Parameter};
+use events::{handle_input, handle_loop, handle_replay_event, LoopOutcome, WindowRequest};
 use gfx_device_gl::{CommandBuffer, Resources};
 use gfx_graphics::GfxGraphics;
-use piston_window::*;
+use keymap::{Action, KeyBinding, Keymap};
+use lenia::{
+    generate_spawn_area, text_pixels, text_size, AdaptiveKernelRadius, Boundary, BrushShape, Cell,
+    DrawBlend, GenerationsRule, GolRule, Grid, ImageFit, Lenia, Mode, Neighbourhood,
+    PeriodDetector, Potential, Preset, ResizeAnchor, Saturation, Symmetry, Topology, UpdateOrder,
+    GLYPH_SIZE, MIN_GRID_SIZE,
+};
+use piston_window::{
+    texture::{CreateTexture, UpdateTexture},
+    *,
+};
+use replay::{Replay, ReplayEvent, ReplayEventKind};
+use std::{
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use watch::ParamsWatcher;
 
 const CELL_DIMENSION: f64 = 2.0;
 
 const DEFAULT_WIDTH: u32 = 256;
 const DEFAULT_HEIGHT: u32 = 256;
 
+const MAX_BRUSH_RADIUS: usize = 32;
+
+/// cells below this value are reseeded by [`Action::ReseedEmpty`], the others count as structures
+const EMPTY_CELL_THRESHOLD: Cell = 0.05;
+
+/// cells changing less than this over a step count as unchanged, see [`AppState::auto_pause_frames`]
+const STABLE_CELL_CHANGE: Cell = 1e-5;
+
+/// where `--autosave` writes the grid on exit, along with the generation and the random generator
+/// state, and `--recover` loads it from, see [`Lenia::save_state`]
+const RECOVERY_PATH: &str = "lenia-recovery.toml";
+
+/// loaded on startup from the working directory, and rewritten when a preset is saved
+const PRESETS_PATH: &str = "presets.toml";
+
+/// radius (in pixels) of the circle around the highest cell, see [`AppState::show_hotspot`]
+const HOTSPOT_MARKER_RADIUS: f64 = 6.0;
+
+/// frames remembered by the period detector, the longest oscillator period it can find, see
+/// [`Action::TogglePeriodDetection`]
+const PERIOD_HISTORY: usize = 1000;
+
+/// below this size (in pixels) grid lines would hide the cells, so they aren't drawn
+const MIN_GRID_LINES_CELL_DIMENSION: f64 = 4.0;
+
 const DEFAULT_WCELL_COUNT: usize = DEFAULT_WIDTH as usize / CELL_DIMENSION as usize;
 const DEFAULT_HCELL_COUNT: usize = DEFAULT_HEIGHT as usize / CELL_DIMENSION as usize;
 
-enum Mode {
-    Lenia,
-    GameOfLife,
+/// background and cell colors
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum Theme {
+    /// white cells over black
+    #[default]
+    Dark,
+    /// dark cells over white, better for printing
+    Light,
 }
 
-struct Lenia {
-    cells: Vec<Vec<f32>>,
-    active_cells: AHashSet<(usize, usize)>,
-    mode: Mode,
-    delta_t: f64,
-    /// in cells width
-    kernel_radius: usize,
-}
+impl Theme {
+    fn background_color(self) -> [f32; 4] {
+        match self {
+            Theme::Dark => [0.0, 0.0, 0.0, 1.0],
+            Theme::Light => [1.0, 1.0, 1.0, 1.0],
+        }
+    }
 
-impl Lenia {
-    fn new(
-        (wcell_count, hcell_count): (usize, usize),
-        ((area_w_min, mut area_w_max), (area_h_min, mut area_h_max)): (
-            (usize, usize),
-            (usize, usize),
-        ),
-        mode: Option<Mode>,
-        delta_t: Option<f64>,
-        kernel_radius: Option<usize>,
-    ) -> Self {
-        if area_w_max >= wcell_count {
-            area_w_max = wcell_count - 1;
+    /// `color`, as seen over the dark background, inverted over the light one so that it stays
+    /// visible
+    fn cell_color(self, [red, green, blue]: [f32; 3]) -> [f32; 3] {
+        match self {
+            Theme::Dark => [red, green, blue],
+            Theme::Light => [1.0 - red, 1.0 - green, 1.0 - blue],
         }
-        if area_h_max >= hcell_count {
-            area_h_max = hcell_count - 1;
+    }
+}
+
+/// what the mouse wheel tunes, see [`AppState::scroll`]; mu and sigma are the ones of the first
+/// kernel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum TunedParameter {
+    #[default]
+    BrushRadius,
+    Mu,
+    Sigma,
+    KernelRadius,
+    DeltaT,
+}
+
+impl TunedParameter {
+    fn next(self) -> Self {
+        match self {
+            TunedParameter::BrushRadius => TunedParameter::Mu,
+            TunedParameter::Mu => TunedParameter::Sigma,
+            TunedParameter::Sigma => TunedParameter::KernelRadius,
+            TunedParameter::KernelRadius => TunedParameter::DeltaT,
+            TunedParameter::DeltaT => TunedParameter::BrushRadius,
         }
+    }
 
-        let mut cells = vec![vec![0_f32; wcell_count]; hcell_count];
-        for raw in cells.iter_mut().take(area_h_max + 1).skip(area_h_min) {
-            for cell in raw.iter_mut().take(area_w_max + 1).skip(area_w_min) {
-                *cell = match mode.as_ref().unwrap_or(&Mode::Lenia) {
-                    Mode::Lenia => fastrand::f32(),
-                    Mode::GameOfLife => fastrand::usize(0..=1) as f32,
-                }
-            }
+    /// its name and current value
+    fn describe(self, app_state: &AppState, lenia: &Lenia) -> String {
+        match self {
+            TunedParameter::BrushRadius => format!("brush radius {}", app_state.brush_radius),
+            TunedParameter::Mu => format!("mu {:.3}", lenia.kernels[0].mu),
+            TunedParameter::Sigma => format!("sigma {:.4}", lenia.kernels[0].sigma),
+            TunedParameter::KernelRadius => match lenia.kernel_radius {
+                (radius_x, radius_y) if radius_x == radius_y => format!("kernel radius {radius_x}"),
+                (radius_x, radius_y) => format!("kernel radius {radius_x}x{radius_y}"),
+            },
+            TunedParameter::DeltaT => format!("delta_t {:.2}", lenia.delta_t),
         }
+    }
+}
+
+/// smooth rendering: the cells as a texture of one texel per cell, that the GPU stretches over
+/// the grid with bilinear filtering, down to the pixel, repeating at the edges as the field
+/// wraps around them
+struct SmoothRenderer {
+    texture_context: G2dTextureContext,
+    /// recreated when the grid is resized
+    texture: Option<G2dTexture>,
+    /// RGBA bytes of the cells, raw after raw
+    pixels: Vec<u8>,
+}
 
+impl SmoothRenderer {
+    fn new(window: &mut PistonWindow) -> Self {
         Self {
-            cells,
-            active_cells: AHashSet::new(),
-            mode: mode.unwrap_or(Mode::Lenia),
-            delta_t: delta_t.unwrap_or(1.0),
-            kernel_radius: kernel_radius.unwrap_or(13),
+            texture_context: window.create_texture_context(),
+            texture: None,
+            pixels: Vec::new(),
         }
     }
 
-    fn resize(&mut self, new_cell_width_count: usize, new_cell_height_count: usize) {
-        if new_cell_width_count != self.cells[0].len() {
-            for raw in 0..(self.cells.len()) {
-                self.cells[raw].resize(new_cell_width_count, 0.0);
+    /// upload the cells, colored as [`render`] does, to the texture; the upload happens when
+    /// the texture context is flushed, in `draw_2d`
+    fn update(&mut self, lenia: &Lenia, app_state: &AppState) -> Result<(), String> {
+        let threshold = app_state
+            .is_render_quantized
+            .then_some(app_state.render_threshold);
+        let (w, h) = (lenia.cells[0].len(), lenia.cells.len());
+        self.pixels.clear();
+        for raw in 0..h {
+            for col in 0..w {
+                let [red, green, blue] =
+                    app_state.theme.cell_color(if app_state.is_render_by_age {
+                        age_color(lenia.age[raw][col])
+                    } else {
+                        [1.0, 1.0, 1.0]
+                    });
+                let alpha = cell_alpha(lenia.cells[raw][col], threshold);
+                self.pixels.extend(
+                    [red, green, blue, alpha]
+                        .map(|channel| (channel.clamp(0.0, 1.0) * 255.0) as u8),
+                );
             }
         }
-        if new_cell_height_count != self.cells.len() {
-            self.cells
-                .resize(new_cell_height_count, vec![0.0; new_cell_width_count]);
+
+        let size = [w as u32, h as u32];
+        match &mut self.texture {
+            Some(texture) if texture.get_size() == (size[0], size[1]) => UpdateTexture::update(
+                texture,
+                &mut self.texture_context,
+                texture::Format::Rgba8,
+                &self.pixels,
+                [0, 0],
+                size,
+            )
+            .map_err(|err| err.to_string()),
+            texture => {
+                let settings = TextureSettings::new()
+                    .filter(texture::Filter::Linear)
+                    .wrap_u(texture::Wrap::Repeat)
+                    .wrap_v(texture::Wrap::Repeat);
+                *texture = Some(
+                    Texture::create(
+                        &mut self.texture_context,
+                        texture::Format::Rgba8,
+                        &self.pixels,
+                        size,
+                        &settings,
+                    )
+                    .map_err(|err| err.to_string())?,
+                );
+                Ok(())
+            }
         }
     }
 
-    fn compute_next_frame(&mut self) {
-        match self.mode {
-            Mode::Lenia => self.compute_next_lenia_frame(),
-            Mode::GameOfLife => self.compute_next_gol_frame(),
+    fn draw(
+        &self,
+        lenia: &Lenia,
+        app_state: &AppState,
+        context: Context,
+        graphics: &mut GfxGraphics<'_, Resources, CommandBuffer>,
+    ) {
+        if let Some(texture) = &self.texture {
+            let (width, height) = app_state.grid_to_screen(lenia.cells.len(), lenia.cells[0].len());
+            Image::new().rect([0.0, 0.0, width, height]).draw(
+                texture,
+                &context.draw_state,
+                context.transform,
+                graphics,
+            );
         }
     }
+}
 
-    fn compute_next_lenia_frame(&mut self) {
-        let (w, h) = (self.cells[0].len(), self.cells.len());
-        fn kernel_core_function(distance_from_cell: usize, kernel_radius: usize) -> f64 {
-            const ALPHA: f64 = 4.0;
-            let r = ((distance_from_cell as f64 / kernel_radius as f64) * 10.0).round() / 10.0;
-            (ALPHA * (1.0 - 1.0 / (ALPHA * r * (1.0 - r)))).exp()
-        }
-        fn growth_function(potential_distribution: f64) -> f64 {
-            const MU: f64 = 0.31;
-            const SIGMA: f64 = 0.049;
-            const K: f64 = 2.0 * SIGMA * SIGMA;
+/// draw every cell as a white square (or colored by age) over the background of the theme, its
+/// value being the opacity, or with `smooth` the cells uploaded by [`SmoothRenderer::update`]
+fn render(
+    lenia: &Lenia,
+    app_state: &AppState,
+    smooth: Option<&SmoothRenderer>,
+    context: Context,
+    graphics: &mut GfxGraphics<'_, Resources, CommandBuffer>,
+) {
+    let threshold = app_state
+        .is_render_quantized
+        .then_some(app_state.render_threshold);
+    let w = lenia.cells[0].len();
+    // a reference of other dimensions, from before a resize, isn't compared
+    let diff = match &app_state.reference_cells {
+        Some(reference) if app_state.show_diff => lenia.signed_diff(reference),
+        _ => None,
+    };
+    match (diff, smooth) {
+        (Some(diff), _) => render_diff(&diff, app_state, context, graphics),
+        (None, Some(smooth)) => smooth.draw(lenia, app_state, context, graphics),
+        (None, None) => fill_cell_runs(
+            (w, lenia.cells.len()),
+            |raw, col| {
+                let [red, green, blue] =
+                    app_state.theme.cell_color(if app_state.is_render_by_age {
+                        age_color(lenia.age[raw][col])
+                    } else {
+                        [1.0, 1.0, 1.0]
+                    });
+                [
+                    red,
+                    green,
+                    blue,
+                    cell_alpha(lenia.cells[raw][col], threshold),
+                ]
+            },
+            app_state,
+            context,
+            graphics,
+        ),
+    }
 
-            let l = (potential_distribution - MU).abs();
-            2.0 * (-(l * l) / K).exp() - 1.0
+    if app_state.show_grid_lines && CELL_DIMENSION >= MIN_GRID_LINES_CELL_DIMENSION {
+        let (grid_width, grid_height) = app_state.grid_to_screen(lenia.cells.len(), w);
+        for col in 1..w {
+            let (x, _) = app_state.grid_to_screen(0, col);
+            line_from_to(
+                app_state.grid_line_color,
+                0.5,
+                [x, 0.0],
+                [x, grid_height],
+                context.transform,
+                graphics,
+            );
         }
+        for raw in 1..lenia.cells.len() {
+            let (_, y) = app_state.grid_to_screen(raw, 0);
+            line_from_to(
+                app_state.grid_line_color,
+                0.5,
+                [0.0, y],
+                [grid_width, y],
+                context.transform,
+                graphics,
+            );
+        }
+    }
 
-        let mut next_frame_cells = self.cells.clone();
-        #[allow(clippy::needless_range_loop)]
-        for raw in 0..h {
-            for col in 0..w {
-                let mut potential_distribution = 0.0;
-                let mut max_kernel = 0.0;
-                for neighbour_raw in (raw as isize - self.kernel_radius as isize)
-                    ..=(raw as isize + self.kernel_radius as isize)
-                {
-                    for neighbour_col in (col as isize - self.kernel_radius as isize)
-                        ..=(col as isize + (self.kernel_radius) as isize)
-                    {
-                        if neighbour_raw == raw as isize && neighbour_col == col as isize {
-                            continue;
-                        }
-                        let distance_from_cell = (raw as isize - neighbour_raw).unsigned_abs()
-                            + (col as isize - neighbour_col).unsigned_abs();
-                        if distance_from_cell > self.kernel_radius {
-                            continue;
-                        }
-                        let kernel_val =
-                            kernel_core_function(distance_from_cell, self.kernel_radius);
-                        max_kernel += kernel_val;
-
-                        let (xpos, ypos) = (
-                            neighbour_col.rem_euclid(w as isize - 1) as usize,
-                            neighbour_raw.rem_euclid(h as isize - 1) as usize,
-                        );
-                        potential_distribution += self.cells[ypos][xpos] as f64 * kernel_val;
-                    }
-                }
-                potential_distribution /= max_kernel;
-
-                let growth_mapping = growth_function(potential_distribution);
-                let next_frame_value = (self.cells[raw][col] as f64 + self.delta_t * growth_mapping)
-                    .clamp(0.0, 1.0) as f32;
-                next_frame_cells[raw][col] = next_frame_value;
-            }
+    // cells the next Game of Life step recomputes are tinted green
+    if app_state.show_active_cells {
+        for &(raw, col) in &lenia.active_cells {
+            let (x, y) = app_state.grid_to_screen(raw, col);
+            rectangle(
+                [0.2, 1.0, 0.2, 0.3],
+                [x, y, CELL_DIMENSION, CELL_DIMENSION],
+                context.transform,
+                graphics,
+            );
         }
-        self.cells = next_frame_cells; // update to next frame
     }
 
-    fn compute_next_gol_frame(&mut self) {
-        let (w, h) = (self.cells[0].len(), self.cells.len());
+    // walls are tinted red, brighter when frozen alive
+    for &(raw, col) in &lenia.walls {
+        let (x, y) = app_state.grid_to_screen(raw, col);
+        rectangle(
+            [1.0, 0.2, 0.2, (lenia.cells[raw][col] as f32).max(0.3)],
+            [x, y, CELL_DIMENSION, CELL_DIMENSION],
+            context.transform,
+            graphics,
+        );
+    }
 
-        let mut next_frame_cells = self.cells.clone();
-        let mut next_frame_active_cells: AHashSet<(usize, usize)> = AHashSet::new();
+    // the highest cell is circled in magenta, large enough to be found at a glance
+    if app_state.show_hotspot {
+        let (raw, col) = lenia.cell_extremes().max_cell;
+        let (x, y) = app_state.grid_to_screen(raw, col);
+        let (center_x, center_y) = (x + CELL_DIMENSION / 2.0, y + CELL_DIMENSION / 2.0);
+        Ellipse::new_border([1.0, 0.0, 1.0, 1.0], 1.0).draw(
+            ellipse::circle(center_x, center_y, HOTSPOT_MARKER_RADIUS),
+            &context.draw_state,
+            context.transform,
+            graphics,
+        );
+    }
+}
 
-        let mut update_cell = |raw: usize, col: usize| {
-            let (top_raw, bottom_raw) = (
-                if raw == 0 { h - 1 } else { raw - 1 },
-                if raw == h - 1 { 0 } else { raw + 1 },
+/// every `spacing`th cell index of a row (column) of `cell_count` cells, the first one excepted,
+/// with its offset in pixels from the grid edge, where [`render_ruler`] draws its lines
+fn ruler_lines(spacing: usize, cell_count: usize) -> impl Iterator<Item = (usize, f64)> {
+    (spacing.max(1)..cell_count)
+        .step_by(spacing.max(1))
+        .map(|cell| (cell, cell as f64 * CELL_DIMENSION))
+}
+
+/// faint lines every [`AppState::ruler_spacing`] cells, labelled with their cell index along the
+/// top and left edges, to measure patterns
+fn render_ruler(
+    lenia: &Lenia,
+    app_state: &AppState,
+    context: Context,
+    graphics: &mut GfxGraphics<'_, Resources, CommandBuffer>,
+) {
+    let (w, h) = (lenia.cells[0].len(), lenia.cells.len());
+    let (grid_width, grid_height) = app_state.grid_to_screen(h, w);
+    let [red, green, blue] = app_state.theme.cell_color([0.3, 0.7, 1.0]);
+    let line_color = [red, green, blue, 0.35];
+    let label_color = [red, green, blue, 0.9];
+    // one pixel per pixel of the 3x5 font, a couple of pixels away from the line
+    let draw_label = |cell: usize, (x, y): (f64, f64), graphics: &mut GfxGraphics<'_, _, _>| {
+        for (raw, col) in text_pixels(&cell.to_string()) {
+            rectangle(
+                label_color,
+                [x + 2.0 + col as f64, y + 2.0 + raw as f64, 1.0, 1.0],
+                context.transform,
+                graphics,
             );
-            let (left_col, right_col) = (
-                if col == 0 { w - 1 } else { col - 1 },
-                if col == w - 1 { 0 } else { col + 1 },
+        }
+    };
+    for (col, x) in ruler_lines(app_state.ruler_spacing, w) {
+        line_from_to(
+            line_color,
+            0.5,
+            [x, 0.0],
+            [x, grid_height],
+            context.transform,
+            graphics,
+        );
+        draw_label(col, (x, 0.0), graphics);
+    }
+    for (raw, y) in ruler_lines(app_state.ruler_spacing, h) {
+        line_from_to(
+            line_color,
+            0.5,
+            [0.0, y],
+            [grid_width, y],
+            context.transform,
+            graphics,
+        );
+        draw_label(raw, (0.0, y), graphics);
+    }
+}
+
+/// every key binding over a translucent backdrop, the simulation going on underneath: as many
+/// columns as the window height needs, at the largest scale of the font the window width allows
+fn render_help(
+    app_state: &AppState,
+    context: Context,
+    graphics: &mut GfxGraphics<'_, Resources, CommandBuffer>,
+) {
+    let [width, height] = context.get_view_size();
+    let [red, green, blue, _] = app_state.theme.background_color();
+    rectangle(
+        [red, green, blue, 0.8],
+        [0.0, 0.0, width, height],
+        context.transform,
+        graphics,
+    );
+    let [red, green, blue] = app_state.theme.cell_color([1.0, 1.0, 1.0]);
+    let text_color = [red, green, blue, 1.0];
+    let (glyph_w, glyph_h) = GLYPH_SIZE;
+    let margin = 2.0;
+    let line_width = app_state
+        .help_lines
+        .iter()
+        .map(|line| text_size(line).0)
+        .max()
+        .unwrap_or(0) as f64;
+    // a blank glyph between columns
+    let column_gap = (glyph_w + 1) as f64;
+    let line_height = (glyph_h + 1) as f64;
+    let mut scale = 4.0;
+    let (lines_per_column, column_width) = loop {
+        let lines_per_column = ((height - 2.0 * margin) / (line_height * scale)).max(1.0) as usize;
+        let columns = app_state.help_lines.len().div_ceil(lines_per_column) as f64;
+        let column_width = (line_width + column_gap) * scale;
+        if columns * column_width - column_gap * scale <= width - 2.0 * margin || scale == 1.0 {
+            break (lines_per_column, column_width);
+        }
+        scale -= 1.0;
+    };
+    for (index, line) in app_state.help_lines.iter().enumerate() {
+        let x = margin + (index / lines_per_column) as f64 * column_width;
+        let y = margin + (index % lines_per_column) as f64 * line_height * scale;
+        for (raw, col) in text_pixels(line) {
+            rectangle(
+                text_color,
+                [x + col as f64 * scale, y + raw as f64 * scale, scale, scale],
+                context.transform,
+                graphics,
             );
+        }
+    }
+}
 
-            let neighbours = [
-                self.cells[top_raw][left_col],     // top left
-                self.cells[top_raw][col],          // top mid
-                self.cells[top_raw][right_col],    // top right
-                self.cells[raw][left_col],         // mid left
-                self.cells[raw][right_col],        // mid right
-                self.cells[bottom_raw][left_col],  // bottom left
-                self.cells[bottom_raw][col],       // bottom mid
-                self.cells[bottom_raw][right_col], // bottom right
-            ];
-
-            let is_alive = self.cells[raw][col] == 1.0;
-            let alive_cells_count = neighbours.into_iter().sum::<f32>() as usize;
-
-            if (is_alive && (2..=3).contains(&alive_cells_count))
-                || (!is_alive && alive_cells_count == 3)
-            {
-                next_frame_cells[raw][col] = 1.0;
+/// cells grown since the reference snapshot in red, shrunk in blue, the largest difference
+/// being opaque
+fn render_diff(
+    diff: &[Vec<Cell>],
+    app_state: &AppState,
+    context: Context,
+    graphics: &mut GfxGraphics<'_, Resources, CommandBuffer>,
+) {
+    let max_abs_difference = diff
+        .iter()
+        .flatten()
+        .fold(0.0, |max: Cell, difference| max.max(difference.abs()));
+    if max_abs_difference == 0.0 {
+        return;
+    }
+    fill_cell_runs(
+        (diff[0].len(), diff.len()),
+        |raw, col| {
+            let difference = diff[raw][col];
+            let alpha = (difference.abs() / max_abs_difference) as f32;
+            if difference > 0.0 {
+                [1.0, 0.2, 0.2, alpha]
             } else {
-                next_frame_cells[raw][col] = 0.0;
+                [0.2, 0.4, 1.0, alpha]
             }
+        },
+        app_state,
+        context,
+        graphics,
+    );
+}
 
-            // change detected, add all affected cells (neighbours and current cells)
-            if self.cells[raw][col] != next_frame_cells[raw][col] {
-                next_frame_active_cells.insert((raw, col));
-                next_frame_active_cells.insert((top_raw, left_col));
-                next_frame_active_cells.insert((top_raw, col));
-                next_frame_active_cells.insert((top_raw, right_col));
-                next_frame_active_cells.insert((raw, left_col));
-                next_frame_active_cells.insert((raw, right_col));
-                next_frame_active_cells.insert((bottom_raw, left_col));
-                next_frame_active_cells.insert((bottom_raw, col));
-                next_frame_active_cells.insert((bottom_raw, right_col));
-            }
-        };
-
-        if self.active_cells.is_empty() {
-            for raw in 0..h {
-                for col in 0..w {
-                    update_cell(raw, col);
-                }
+/// fill every cell of a (w, h) grid with its `color`: one rectangle per horizontal run of cells
+/// of the same color instead of one per cell, as a large grid is mostly runs of dead cells,
+/// skipped altogether as they're transparent
+fn fill_cell_runs(
+    (w, h): (usize, usize),
+    color: impl Fn(usize, usize) -> [f32; 4],
+    app_state: &AppState,
+    context: Context,
+    graphics: &mut GfxGraphics<'_, Resources, CommandBuffer>,
+) {
+    for raw in 0..h {
+        let mut col = 0;
+        while col < w {
+            let (run_start, run_color) = (col, color(raw, col));
+            col += 1;
+            while col < w && color(raw, col) == run_color {
+                col += 1;
             }
-        } else {
-            for &(raw, col) in &self.active_cells {
-                update_cell(raw, col);
+            if run_color[3] == 0.0 {
+                continue;
             }
+            let (x, y) = app_state.grid_to_screen(raw, run_start);
+            rectangle(
+                run_color,
+                [
+                    x,
+                    y,
+                    (col - run_start) as f64 * CELL_DIMENSION,
+                    CELL_DIMENSION,
+                ],
+                context.transform,
+                graphics,
+            );
         }
+    }
+}
 
-        self.cells = next_frame_cells;
-        self.active_cells = next_frame_active_cells;
+/// the weights of the first kernel table, centered in the window as grayscale cells scaled to
+/// the heaviest one
+fn render_kernel(
+    lenia: &Lenia,
+    app_state: &AppState,
+    context: Context,
+    graphics: &mut GfxGraphics<'_, Resources, CommandBuffer>,
+) {
+    let weights = &lenia.kernels[0].table.weights;
+    let max_weight = weights
+        .iter()
+        .map(|&(_, _, weight)| weight)
+        .fold(0.0, f64::max);
+    let [width, height] = context.get_view_size();
+    let [red, green, blue] = app_state.theme.cell_color([1.0, 1.0, 1.0]);
+    for &(draw, dcol, weight) in weights.iter() {
+        rectangle(
+            [red, green, blue, (weight / max_weight) as f32],
+            [
+                width / 2.0 + (dcol as f64 - 0.5) * CELL_DIMENSION,
+                height / 2.0 + (draw as f64 - 0.5) * CELL_DIMENSION,
+                CELL_DIMENSION,
+                CELL_DIMENSION,
+            ],
+            context.transform,
+            graphics,
+        );
     }
+}
 
-    fn render(&self, context: Context, graphics: &mut GfxGraphics<'_, Resources, CommandBuffer>) {
-        let w = self.cells[0].len();
-        for raw in 0..self.cells.len() {
-            for col in 0..w {
-                rectangle(
-                    [1.0, 1.0, 1.0, self.cells[raw][col]], // red
-                    [
-                        col as f64 * CELL_DIMENSION,
-                        raw as f64 * CELL_DIMENSION,
-                        CELL_DIMENSION,
-                        CELL_DIMENSION,
-                    ],
-                    context.transform,
-                    graphics,
-                );
-            }
+/// white for newborn cells, fading to orange then red as they live on
+fn age_color(age: u32) -> [f32; 3] {
+    const OLD_AGE: f32 = 64.0;
+    let oldness = (age as f32 / OLD_AGE).min(1.0);
+    [1.0, 1.0 - 0.8 * oldness, 1.0 - oldness]
+}
+
+/// display opacity of a cell, only affects rendering, not the simulation
+fn cell_alpha(cell: Cell, threshold: Option<Cell>) -> f32 {
+    match threshold {
+        Some(threshold) if cell >= threshold => 1.0,
+        Some(_) => 0.0,
+        None => cell as f32,
+    }
+}
+
+/// write the grid to `dir` as `frame-<generation>.png`, then again after each of the `frames`
+/// steps, the generations zero-padded so that the files sort in order
+#[cfg(feature = "image")]
+fn render_frames(
+    lenia: &mut Lenia,
+    frames: usize,
+    dir: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    std::fs::create_dir_all(dir)?;
+    let digits = (lenia.generation + frames as u64).to_string().len();
+    for frame in 0..=frames {
+        if frame > 0 {
+            lenia.run(1);
         }
+        let path = dir.join(format!("frame-{:0digits$}.png", lenia.generation));
+        lenia.save_image(&path)?;
     }
+    Ok(())
 }
 
-fn generate_spawn_area(wcell_count: usize, hcell_count: usize) -> ((usize, usize), (usize, usize)) {
-    (
-        {
-            let area_w_min = fastrand::usize(0..wcell_count);
-            (
-                area_w_min,
-                area_w_min + fastrand::usize(1..(wcell_count - area_w_min)),
-            )
-        },
-        {
-            let area_h_min = fastrand::usize(0..hcell_count);
-            (
-                area_h_min,
-                area_h_min + fastrand::usize(1..(hcell_count - area_h_min)),
-            )
-        },
-    )
+/// `lenia-<unix time>.<extension>` in the working directory
+fn timestamped_path(extension: &str) -> PathBuf {
+    let unix_time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    PathBuf::from(format!("lenia-{unix_time}.{extension}"))
+}
+
+/// throughput measured since `since`, see [`AppState::benchmark`]
+struct Benchmark {
+    since: Instant,
+    frames: usize,
+    generation: u64,
 }
 
+/// running and drawing are independent: one can draw while the simulation runs, or halt it and
+/// click seeds in place before starting it again
 struct AppState {
     is_game_paused: bool,
+    /// clicks and drags paint (left) or erase (right) only in draw mode
     is_drawing: bool,
-    is_erasing: bool,
+    /// left or right mouse button currently held down
+    held_mouse_button: Option<MouseButton>,
+    /// last known cursor position in window coordinates
+    cursor: Option<[f64; 2]>,
+    /// cell under the cursor, probed in the window title, see [`hud`]
+    hovered_cell: Option<(usize, usize)>,
+    /// (raw, col) painted last in the current stroke, the next one is joined to it by a line
+    last_painted_cell: Option<(usize, usize)>,
+    brush_shape: BrushShape,
+    /// in cells, 0 paints a single cell
+    brush_radius: usize,
+    /// what the mouse wheel tunes
+    tuned_parameter: TunedParameter,
+    /// scroll not yet turned into a whole step of `tuned_parameter`, touchpads scrolling by
+    /// fractions
+    scroll: f64,
+    /// value painted in Lenia mode, from 0.0 to 1.0
+    paint_intensity: Cell,
+    /// how painting combines with the cells, erasing always overwrites
+    draw_blend: DrawBlend,
+    /// fraction of the cells randomized on reseed, see [`Lenia::seed_area`]
+    spawn_density: f32,
+    /// reseed the whole grid with a symmetric soup instead, see [`Lenia::seed_symmetric`], and
+    /// mirror the brush the same way so that painting keeps the soup symmetric
+    seed_symmetry: Option<Symmetry>,
+    /// see [`Lenia::add_noise`]
+    noise_amplitude: f32,
+    /// shift is held: the brush marks (erases) walls instead of painting cells
+    is_painting_walls: bool,
+    /// render cells as binary (on/off) around `render_threshold`
+    is_render_quantized: bool,
+    render_threshold: Cell,
+    /// color cells by how long they have been alive
+    is_render_by_age: bool,
+    /// interpolate the field between cell centers, see [`SmoothRenderer`]
+    is_render_smooth: bool,
+    /// lines between cells, only drawn when cells are large enough
+    show_grid_lines: bool,
+    grid_line_color: [f32; 4],
+    /// labelled lines every `ruler_spacing` cells, see [`render_ruler`]
+    show_ruler: bool,
+    ruler_spacing: usize,
+    theme: Theme,
+    /// tint the cells in [`Lenia::active_cells`]
+    show_active_cells: bool,
+    /// draw the kernel table instead of the cells, see [`render_kernel`]
+    show_kernel: bool,
+    /// list the key bindings over the cells, see [`render_help`]
+    show_help: bool,
+    /// [`Keymap::help_lines`], once the `--bind` options are applied
+    help_lines: Vec<String>,
+    /// cells captured by [`Action::CaptureReference`]
+    reference_cells: Option<Grid>,
+    /// color the cells by their difference from `reference_cells` instead, see [`render_diff`]
+    show_diff: bool,
+    /// circle the highest cell, see [`Lenia::cell_extremes`]
+    show_hotspot: bool,
+    /// while running, only draw every Nth frame
+    render_every: usize,
+    /// simulation steps computed per frame
+    compute_per_render: usize,
+    frames_since_render: usize,
+    /// steps queued by [`Action::Advance`]
+    advance_steps: usize,
+    /// steps left before pausing again, when advancing
+    pending_steps: Option<usize>,
+    /// the simulation halts upon reaching this generation
+    max_frames: Option<u64>,
+    /// the simulation halts once the grid hasn't changed for this many frames in a row
+    auto_pause_frames: Option<usize>,
+    /// consecutive frames without change, see [`STABLE_CELL_CHANGE`]
+    stable_frames: usize,
+    /// compute as fast as possible without drawing, printing the throughput every second
+    benchmark: Option<Benchmark>,
+    /// see [`PRESETS_PATH`]
+    presets: Vec<Preset>,
+    /// last applied preset
+    preset_index: Option<usize>,
+    /// name being typed for the preset to save, keys type instead of triggering actions meanwhile
+    preset_name_input: Option<String>,
 }
 
 impl Default for AppState {
@@ -257,96 +681,1437 @@ impl Default for AppState {
         Self {
             is_game_paused: true,
             is_drawing: false,
-            is_erasing: false,
+            held_mouse_button: None,
+            cursor: None,
+            hovered_cell: None,
+            last_painted_cell: None,
+            brush_shape: BrushShape::Square,
+            brush_radius: 0,
+            tuned_parameter: TunedParameter::default(),
+            scroll: 0.0,
+            paint_intensity: 1.0,
+            draw_blend: DrawBlend::default(),
+            spawn_density: 1.0,
+            seed_symmetry: None,
+            noise_amplitude: 0.1,
+            is_painting_walls: false,
+            is_render_quantized: false,
+            render_threshold: 0.5,
+            is_render_by_age: false,
+            is_render_smooth: false,
+            show_grid_lines: false,
+            grid_line_color: [0.5, 0.5, 0.5, 0.5],
+            show_ruler: false,
+            ruler_spacing: 10,
+            theme: Theme::default(),
+            show_active_cells: false,
+            show_kernel: false,
+            show_help: false,
+            help_lines: Vec::new(),
+            reference_cells: None,
+            show_diff: false,
+            show_hotspot: false,
+            render_every: 1,
+            compute_per_render: 1,
+            frames_since_render: 0,
+            advance_steps: 1,
+            pending_steps: None,
+            max_frames: None,
+            auto_pause_frames: None,
+            stable_frames: 0,
+            benchmark: None,
+            presets: vec![],
+            preset_index: None,
+            preset_name_input: None,
         }
     }
 }
 
+impl AppState {
+    /// raise (scrolling up) or lower the tuned parameter by one step per scroll notch: a cell
+    /// of brush radius within 0..=[`MAX_BRUSH_RADIUS`], 0.005 of mu, 0.001 of sigma, a cell of
+    /// kernel radius within 1..=[`Lenia::max_kernel_radius`] and 0.01 of delta_t
+    fn scroll(&mut self, lenia: &mut Lenia, dy: f64) {
+        self.scroll += dy;
+        let steps = self.scroll.trunc();
+        self.scroll -= steps;
+        if steps == 0.0 {
+            return;
+        }
+        match self.tuned_parameter {
+            TunedParameter::BrushRadius => {
+                self.brush_radius =
+                    (self.brush_radius as f64 + steps).clamp(0.0, MAX_BRUSH_RADIUS as f64) as usize
+            }
+            TunedParameter::Mu => {
+                let mu = &mut lenia.kernels[0].mu;
+                *mu = (*mu + 0.005 * steps).clamp(0.0, 1.0);
+            }
+            TunedParameter::Sigma => {
+                let sigma = &mut lenia.kernels[0].sigma;
+                *sigma = (*sigma + 0.001 * steps).clamp(0.001, 1.0);
+            }
+            TunedParameter::KernelRadius => {
+                lenia.step_kernel_radius(steps as isize);
+            }
+            TunedParameter::DeltaT => {
+                lenia.delta_t = (lenia.delta_t + 0.01 * steps).clamp(0.01, 1.0);
+            }
+        }
+    }
+
+    /// window position of the top-left corner of cell (raw, col)
+    fn grid_to_screen(&self, raw: usize, col: usize) -> (f64, f64) {
+        (col as f64 * CELL_DIMENSION, raw as f64 * CELL_DIMENSION)
+    }
+
+    /// cell under the window position (x, y), `None` outside of the grid
+    fn screen_to_grid(&self, lenia: &Lenia, x: f64, y: f64) -> Option<(usize, usize)> {
+        let (raw, col) = ((y / CELL_DIMENSION).floor(), (x / CELL_DIMENSION).floor());
+        (raw >= 0.0
+            && col >= 0.0
+            && (raw as usize) < lenia.cells.len()
+            && (col as usize) < lenia.cells[0].len())
+        .then_some((raw as usize, col as usize))
+    }
+
+    /// whether this frame is drawn, restarting the count of frames since the last drawn one if
+    /// so: while halted every frame is, so that edits show up right away, while running every
+    /// [`AppState::render_every`]th one
+    fn take_render(&mut self) -> bool {
+        let is_due = self.is_game_paused || self.frames_since_render >= self.render_every;
+        if is_due {
+            self.frames_since_render = 0;
+        }
+        is_due
+    }
+}
+
+/// save the current parameters under the typed name, replacing any preset of the same name
+fn save_preset(app_state: &mut AppState, lenia: &Lenia) {
+    let Some(name) = app_state.preset_name_input.take() else {
+        return;
+    };
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return;
+    }
+    let preset = Preset::from_lenia(name.clone(), lenia);
+    match app_state
+        .presets
+        .iter_mut()
+        .find(|preset| preset.name == name)
+    {
+        Some(existing) => *existing = preset,
+        None => app_state.presets.push(preset),
+    }
+    match Preset::save_all(PRESETS_PATH.as_ref(), &app_state.presets) {
+        Ok(()) => println!("saved preset \"{name}\" to {PRESETS_PATH}"),
+        Err(err) => eprintln!("failed to save {PRESETS_PATH}: {err}"),
+    }
+}
+
+/// paint (or erase) under the cursor, if in draw mode with a mouse button held
+fn paint_at_cursor(app_state: &mut AppState, lenia: &mut Lenia) {
+    let (true, Some(button), Some([x, y])) = (
+        app_state.is_drawing,
+        app_state.held_mouse_button,
+        app_state.cursor,
+    ) else {
+        return;
+    };
+    let Some(cell) = app_state.screen_to_grid(lenia, x, y) else {
+        return;
+    };
+    let is_erasing = button == MouseButton::Right;
+    // fast drags move the cursor by several cells between events, the gaps are filled by a line
+    // from the last dab, itself already painted
+    let cells = match app_state.last_painted_cell.replace(cell) {
+        Some(last_cell) if last_cell != cell => line_cells(last_cell, cell).skip(1).collect(),
+        _ => vec![cell],
+    };
+
+    // Game of Life cells are binary, so only Lenia gets fractional and soft paint
+    let is_lenia = matches!(lenia.mode, Mode::Lenia);
+    let (w, h) = (lenia.cells[0].len(), lenia.cells.len());
+    let centers = cells
+        .into_iter()
+        .flat_map(|cell| match app_state.seed_symmetry {
+            Some(symmetry) => symmetry.images(cell, (w, h)),
+            None => vec![cell],
+        });
+    for center in centers {
+        if app_state.is_painting_walls {
+            lenia.paint_walls(
+                center,
+                app_state.brush_shape,
+                app_state.brush_radius,
+                !is_erasing,
+            );
+            continue;
+        }
+        lenia.paint(
+            center,
+            app_state.brush_shape,
+            app_state.brush_radius,
+            match (is_erasing, is_lenia) {
+                (true, _) => 0.0,
+                (false, true) => app_state.paint_intensity,
+                (false, false) => 1.0,
+            },
+            is_lenia && !is_erasing && app_state.brush_shape == BrushShape::Disc,
+            if is_erasing {
+                DrawBlend::Overwrite
+            } else {
+                app_state.draw_blend
+            },
+        );
+    }
+}
+
+/// (raw, col) of the cells of a Bresenham line, both ends included
+fn line_cells(
+    (from_raw, from_col): (usize, usize),
+    (to_raw, to_col): (usize, usize),
+) -> impl Iterator<Item = (usize, usize)> {
+    let (mut raw, mut col) = (from_raw as isize, from_col as isize);
+    let (to_raw, to_col) = (to_raw as isize, to_col as isize);
+    let (draw, dcol) = ((to_raw - raw).abs(), -(to_col - col).abs());
+    let (step_raw, step_col) = ((to_raw - raw).signum(), (to_col - col).signum());
+    let mut error = draw + dcol;
+    let mut is_done = false;
+    std::iter::from_fn(move || {
+        if is_done {
+            return None;
+        }
+        let cell = (raw as usize, col as usize);
+        is_done = (raw, col) == (to_raw, to_col);
+        let doubled_error = 2 * error;
+        if doubled_error >= dcol {
+            error += dcol;
+            raw += step_raw;
+        }
+        if doubled_error <= draw {
+            error += draw;
+            col += step_col;
+        }
+        Some(cell)
+    })
+}
+
+fn apply_action(action: Action, app_state: &mut AppState, lenia: &mut Lenia) {
+    match action {
+        Action::Reset => match app_state.seed_symmetry {
+            Some(symmetry) => lenia.seed_symmetric(symmetry),
+            None => {
+                let (w, h) = (lenia.cells[0].len(), lenia.cells.len());
+                let area = generate_spawn_area(w, h, &mut lenia.rng);
+                lenia.seed_area(area, app_state.spawn_density);
+            }
+        },
+        Action::RandomizeParams => {
+            lenia.randomize_params();
+            let spec = &lenia.kernels[0];
+            println!(
+                "delta_t {}, kernel radius {}, mu {:.3}, sigma {:.4} (w saves them as a preset)",
+                lenia.delta_t, lenia.kernel_radius.0, spec.mu, spec.sigma
+            );
+            apply_action(Action::Reset, app_state, lenia);
+        }
+        Action::ReseedEmpty => lenia.seed_empty(EMPTY_CELL_THRESHOLD, app_state.spawn_density),
+        Action::Clear => lenia.clear(),
+        Action::ClearInsideBorder => {
+            let (w, h) = (lenia.cells[0].len(), lenia.cells.len());
+            lenia.clear_rect(((1, w.saturating_sub(2)), (1, h.saturating_sub(2))));
+        }
+        Action::DrawBorder => {
+            let (w, h) = (lenia.cells[0].len(), lenia.cells.len());
+            lenia.draw_border(((0, w - 1), (0, h - 1)), 1, 1.0);
+        }
+        Action::Start => {
+            app_state.is_game_paused = false;
+            app_state.pending_steps = None;
+        }
+        Action::Halt => {
+            app_state.is_game_paused = true;
+            app_state.pending_steps = None;
+        }
+        Action::ToggleBenchmark => {
+            app_state.benchmark = match app_state.benchmark {
+                Some(_) => None,
+                None => Some(Benchmark {
+                    since: Instant::now(),
+                    frames: 0,
+                    generation: lenia.generation,
+                }),
+            }
+        }
+        Action::Advance => {
+            app_state.is_game_paused = false;
+            app_state.pending_steps = Some(app_state.advance_steps);
+        }
+        Action::UndoStep => {
+            if lenia.undo_step() {
+                app_state.is_game_paused = true;
+                app_state.pending_steps = None;
+            } else {
+                println!("no step to undo");
+            }
+        }
+        Action::ToggleMode => {
+            let mode = match lenia.mode {
+                Mode::Lenia => Mode::GameOfLife,
+                _ => Mode::Lenia,
+            };
+            lenia.set_mode(mode);
+            // the parameters that matter in the new mode
+            match lenia.mode {
+                Mode::Lenia => println!(
+                    "mode: Lenia (delta_t {}, kernel radius {:?})",
+                    lenia.delta_t, lenia.kernel_radius
+                ),
+                _ => println!("mode: Game of Life ({})", lenia.gol_rule),
+            }
+        }
+        Action::ToggleDraw => app_state.is_drawing = !app_state.is_drawing,
+        Action::CycleDrawBlend => {
+            app_state.draw_blend = match app_state.draw_blend {
+                DrawBlend::Overwrite => DrawBlend::Add,
+                DrawBlend::Add => DrawBlend::Subtract,
+                DrawBlend::Subtract => DrawBlend::Overwrite,
+            };
+            println!("draw blend: {:?}", app_state.draw_blend);
+        }
+        Action::ToggleBrushShape => {
+            app_state.brush_shape = match app_state.brush_shape {
+                BrushShape::Square => BrushShape::Disc,
+                BrushShape::Disc => BrushShape::Square,
+            }
+        }
+        Action::ShrinkBrush => app_state.brush_radius = app_state.brush_radius.saturating_sub(1),
+        Action::GrowBrush => {
+            app_state.brush_radius = (app_state.brush_radius + 1).min(MAX_BRUSH_RADIUS)
+        }
+        Action::LowerPaintIntensity | Action::RaisePaintIntensity => {
+            let step = if action == Action::RaisePaintIntensity {
+                0.1
+            } else {
+                -0.1
+            };
+            app_state.paint_intensity = (app_state.paint_intensity + step).clamp(0.0, 1.0);
+            println!("paint intensity: {:.1}", app_state.paint_intensity);
+        }
+        Action::SaveNpy => {
+            let path = timestamped_path("npy");
+            match lenia.save_npy(&path) {
+                Ok(()) => println!("saved {}", path.display()),
+                Err(err) => eprintln!("failed to save {}: {err}", path.display()),
+            }
+        }
+        Action::SaveRle => {
+            let rle_path = timestamped_path("rle");
+            #[cfg(feature = "macrocell")]
+            let macrocell_path = rle_path.with_extension("mc");
+            for (path, saved) in [
+                (&rle_path, lenia.save_rle(&rle_path)),
+                #[cfg(feature = "macrocell")]
+                (&macrocell_path, lenia.save_macrocell(&macrocell_path)),
+            ] {
+                match saved {
+                    Ok(()) => println!("saved {}", path.display()),
+                    Err(err) => eprintln!("failed to save {}: {err}", path.display()),
+                }
+            }
+        }
+        Action::ToggleQuantizedRender => {
+            app_state.is_render_quantized = !app_state.is_render_quantized
+        }
+        Action::LowerRenderThreshold | Action::RaiseRenderThreshold => {
+            let step = if action == Action::RaiseRenderThreshold {
+                0.05
+            } else {
+                -0.05
+            };
+            app_state.render_threshold = (app_state.render_threshold + step).clamp(0.0, 1.0);
+            println!("render threshold: {:.2}", app_state.render_threshold);
+        }
+        Action::PlotKernelAndGrowth => {
+            #[cfg(feature = "plot")]
+            for (path, plotted) in [
+                ("kernel.png", lenia.plot_kernel("kernel.png".as_ref())),
+                ("growth.png", lenia.plot_growth("growth.png".as_ref())),
+            ] {
+                match plotted {
+                    Ok(()) => println!("saved {path}"),
+                    Err(err) => eprintln!("failed to save {path}: {err}"),
+                }
+            }
+            #[cfg(not(feature = "plot"))]
+            eprintln!("plotting requires the \"plot\" feature");
+        }
+        Action::SavePreset => app_state.preset_name_input = Some(String::new()),
+        Action::NextPreset => {
+            if app_state.presets.is_empty() {
+                eprintln!("no presets in {PRESETS_PATH}");
+                return;
+            }
+            let index = app_state
+                .preset_index
+                .map_or(0, |index| (index + 1) % app_state.presets.len());
+            let preset = &app_state.presets[index];
+            lenia.apply_preset(preset);
+            println!("preset \"{}\"", preset.name);
+            app_state.preset_index = Some(index);
+        }
+        Action::LowerSpawnDensity | Action::RaiseSpawnDensity => {
+            let step = if action == Action::RaiseSpawnDensity {
+                0.1
+            } else {
+                -0.1
+            };
+            app_state.spawn_density = (app_state.spawn_density + step).clamp(0.1, 1.0);
+            println!("spawn density: {:.1}", app_state.spawn_density);
+        }
+        Action::ShrinkKernelRadius | Action::GrowKernelRadius => {
+            let steps = if action == Action::GrowKernelRadius {
+                1
+            } else {
+                -1
+            };
+            let (radius_x, radius_y) = lenia.step_kernel_radius(steps);
+            println!("kernel radius: {radius_x}x{radius_y}");
+        }
+        Action::LowerKernelAlpha | Action::RaiseKernelAlpha => {
+            let step = if action == Action::RaiseKernelAlpha {
+                0.5
+            } else {
+                -0.5
+            };
+            lenia.kernel_alpha = (lenia.kernel_alpha + step).clamp(0.5, 16.0);
+            println!("kernel alpha: {:.1}", lenia.kernel_alpha);
+        }
+        Action::CycleTunedParameter => {
+            app_state.tuned_parameter = app_state.tuned_parameter.next();
+            println!(
+                "mouse wheel: {}",
+                app_state.tuned_parameter.describe(app_state, lenia)
+            );
+        }
+        Action::Invert => lenia.invert(),
+        Action::InjectNoise => lenia.add_noise(app_state.noise_amplitude),
+        Action::LowerNoiseAmplitude | Action::RaiseNoiseAmplitude => {
+            let step = if action == Action::RaiseNoiseAmplitude {
+                0.05
+            } else {
+                -0.05
+            };
+            app_state.noise_amplitude = (app_state.noise_amplitude + step).clamp(0.0, 1.0);
+            println!("noise amplitude: {:.2}", app_state.noise_amplitude);
+        }
+        Action::ToggleActiveCells => app_state.show_active_cells = !app_state.show_active_cells,
+        Action::ToggleVonNeumann => {
+            // a range goes to von Neumann as well, and comes back as classic Life
+            lenia.neighbourhood = match lenia.neighbourhood {
+                Neighbourhood::VonNeumann => Neighbourhood::Moore,
+                _ => Neighbourhood::VonNeumann,
+            };
+            // the active cells were gathered with the previous neighbours
+            lenia.scan_all_cells = true;
+            println!("neighbourhood: {}", lenia.neighbourhood);
+        }
+        Action::ToggleHotspot => app_state.show_hotspot = !app_state.show_hotspot,
+        Action::TogglePeriodDetection => {
+            lenia.period_detector = match lenia.period_detector {
+                Some(_) => None,
+                None => Some(PeriodDetector::new(PERIOD_HISTORY)),
+            };
+        }
+        Action::CaptureReference => {
+            app_state.reference_cells = Some(lenia.cells.clone());
+            println!(
+                "reference snapshot taken at generation {}",
+                lenia.generation
+            );
+        }
+        Action::ToggleDiffView => {
+            app_state.show_diff = !app_state.show_diff;
+            if app_state.show_diff && app_state.reference_cells.is_none() {
+                println!("no reference snapshot to compare with yet");
+            }
+        }
+        Action::ToggleKernelView => {
+            app_state.show_kernel = !app_state.show_kernel;
+            // the tables are otherwise only built by the first step
+            lenia.sim_params();
+        }
+        Action::ToggleRuler => app_state.show_ruler = !app_state.show_ruler,
+        Action::ToggleWrapX | Action::ToggleWrapY => {
+            // the toggled edges are joined straight
+            let boundary = &mut lenia.boundary;
+            if action == Action::ToggleWrapX {
+                boundary.wrap_x = !boundary.wrap_x;
+                boundary.flip_x = false;
+            } else {
+                boundary.wrap_y = !boundary.wrap_y;
+                boundary.flip_y = false;
+            }
+            // the cells along the edge have other neighbours now
+            lenia.scan_all_cells = true;
+            println!("boundary: {}", describe_boundary(lenia.boundary));
+        }
+        Action::CycleTopology => {
+            lenia.boundary = lenia.boundary.topology().next().boundary();
+            lenia.scan_all_cells = true;
+            println!("boundary: {}", describe_boundary(lenia.boundary));
+        }
+        Action::ToggleGridLines => {
+            app_state.show_grid_lines = !app_state.show_grid_lines;
+            if app_state.show_grid_lines && CELL_DIMENSION < MIN_GRID_LINES_CELL_DIMENSION {
+                println!(
+                    "grid lines are hidden while cells are under {MIN_GRID_LINES_CELL_DIMENSION}px"
+                );
+            }
+        }
+        Action::ToggleTheme => {
+            app_state.theme = match app_state.theme {
+                Theme::Dark => Theme::Light,
+                Theme::Light => Theme::Dark,
+            }
+        }
+        Action::ToggleSmoothRender => app_state.is_render_smooth = !app_state.is_render_smooth,
+        Action::ToggleAgeRender => app_state.is_render_by_age = !app_state.is_render_by_age,
+        Action::RenderLessOften => app_state.render_every += 1,
+        Action::RenderMoreOften => app_state.render_every = (app_state.render_every - 1).max(1),
+        Action::MoreStepsPerFrame => app_state.compute_per_render += 1,
+        Action::FewerStepsPerFrame => {
+            app_state.compute_per_render = (app_state.compute_per_render - 1).max(1)
+        }
+        Action::ToggleHelp => app_state.show_help = !app_state.show_help,
+        Action::ToggleMassConservation => {
+            lenia.conserve_mass = !lenia.conserve_mass;
+            println!(
+                "mass conservation {}",
+                if lenia.conserve_mass { "on" } else { "off" }
+            );
+        }
+    }
+}
+
+/// the surface the edges of the grid make, and which way they're joined when it matters
+fn describe_boundary(boundary: Boundary) -> String {
+    let topology = boundary.topology();
+    match topology {
+        Topology::Cylinder | Topology::MobiusStrip if boundary.wrap_x => {
+            format!("{topology}, wrapping horizontally")
+        }
+        Topology::Cylinder | Topology::MobiusStrip => format!("{topology}, wrapping vertically"),
+        Topology::KleinBottle if boundary.flip_x => {
+            format!("{topology}, flipped horizontally")
+        }
+        Topology::KleinBottle => format!("{topology}, flipped vertically"),
+        _ => topology.to_string(),
+    }
+}
+
+/// stats shown in the window title
+fn hud(app_state: &AppState, lenia: &Lenia) -> String {
+    if let Some(name) = &app_state.preset_name_input {
+        return format!("Lenia! | preset name (enter to save, escape to cancel): {name}_");
+    }
+    let extremes = lenia.cell_extremes();
+    // the grid may have shrunk under the cursor since it last moved
+    let hovered = app_state
+        .hovered_cell
+        .filter(|&(raw, col)| raw < lenia.cells.len() && col < lenia.cells[0].len())
+        .map(|cell| {
+            let value = lenia.cells[cell.0][cell.1];
+            match lenia.mode {
+                Mode::Lenia => {
+                    let potentials = lenia
+                        .potentials_at(cell)
+                        .iter()
+                        .map(|potential| format!("{potential:.3}"))
+                        .collect::<Vec<_>>()
+                        .join("/");
+                    format!(" | cell {cell:?} {value:.3}, potential {potentials}")
+                }
+                _ => format!(" | cell {cell:?} {value:.3}"),
+            }
+        })
+        .unwrap_or_default();
+    format!(
+        "Lenia! | gen {} | mass {:.1} ({:+.2}/frame){} | min {:.3} at {:?}, max {:.3} at {:?} | \
+         drawing 1/{} frames, {} steps/frame | wheel: {}{}{}",
+        lenia.generation,
+        lenia.total_mass(),
+        lenia.mass_drift,
+        match lenia.target_mass {
+            Some(target_mass) => format!(" held at {target_mass:.1}"),
+            None if lenia.conserve_mass => " conserved".to_string(),
+            None => String::new(),
+        },
+        extremes.min,
+        extremes.min_cell,
+        extremes.max,
+        extremes.max_cell,
+        app_state.render_every,
+        app_state.compute_per_render,
+        app_state.tuned_parameter.describe(app_state, lenia),
+        match lenia
+            .period_detector
+            .as_ref()
+            .map(|detector| detector.period)
+        {
+            Some(Some(1)) => " | still life".to_string(),
+            Some(Some(period)) => format!(" | period {period}"),
+            Some(None) => format!(" | no period within {PERIOD_HISTORY} frames"),
+            None => String::new(),
+        },
+        hovered,
+    )
+}
+
+/// do what [`handle_input`] asks of the window
+fn apply_window_request(request: Option<WindowRequest>, window: &mut PistonWindow) {
+    match request {
+        Some(WindowRequest::Close) => window.set_should_close(true),
+        Some(WindowRequest::BenchMode(is_on)) => window.set_bench_mode(is_on),
+        None => (),
+    }
+}
+
+/// open the window; winit panics instead of failing when no display backend is available, the
+/// panic being turned into an error too
+fn build_window() -> Result<PistonWindow, String> {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(|_| {}));
+    let built = std::panic::catch_unwind(|| {
+        WindowSettings::new("Lenia!", [DEFAULT_WIDTH, DEFAULT_HEIGHT]).build::<PistonWindow>()
+    });
+    std::panic::set_hook(default_hook);
+    match built {
+        Ok(built) => built.map_err(|err| err.to_string()),
+        Err(panic) => Err(panic
+            .downcast_ref::<String>()
+            .cloned()
+            .or_else(|| {
+                panic
+                    .downcast_ref::<&str>()
+                    .map(|message| message.to_string())
+            })
+            .unwrap_or_else(|| "the windowing backend panicked".to_string())),
+    }
+}
+
+/// apply a command read from stdin, see [`command::spawn_stdin_reader`]
+fn apply_command(command: Command, app_state: &mut AppState, lenia: &mut Lenia) {
+    match command {
+        Command::Step(steps) => {
+            app_state.is_game_paused = steps == 0;
+            app_state.pending_steps = Some(steps).filter(|&steps| steps > 0);
+        }
+        Command::Start => apply_action(Action::Start, app_state, lenia),
+        Command::Pause => apply_action(Action::Halt, app_state, lenia),
+        Command::Reseed => apply_action(Action::Reset, app_state, lenia),
+        Command::Clear => apply_action(Action::Clear, app_state, lenia),
+        Command::Set(parameter, value) => match parameter {
+            Parameter::DeltaT => lenia.delta_t = value,
+            Parameter::Mu => lenia.kernels[0].mu = value,
+            Parameter::Sigma => lenia.kernels[0].sigma = value,
+            Parameter::KernelRadius => {
+                lenia.kernel_radius = (value as usize, value as usize);
+                // like the radius keys, a kernel wider than the grid is narrowed to fit it
+                if lenia.clamp_kernel_radius() {
+                    println!(
+                        "kernel radius clamped to {:?} to fit the grid",
+                        lenia.kernel_radius
+                    );
+                }
+            }
+            Parameter::KernelAlpha => lenia.kernel_alpha = value,
+        },
+        Command::Save(path) => {
+            let saved = match path.extension().and_then(|extension| extension.to_str()) {
+                Some("rle") => lenia.save_rle(&path),
+                #[cfg(feature = "macrocell")]
+                Some("mc") => lenia.save_macrocell(&path),
+                _ => lenia.save_npy(&path),
+            };
+            match saved {
+                Ok(()) => println!("saved {}", path.display()),
+                Err(err) => eprintln!("failed to save {}: {err}", path.display()),
+            }
+        }
+    }
+}
+
+/// `13` for a circular kernel, `10x16` for an elliptical one (horizontal x vertical), in cells
+struct KernelRadius(usize, usize);
+
+impl FromStr for KernelRadius {
+    type Err = String;
+
+    fn from_str(radius: &str) -> Result<Self, Self::Err> {
+        let parse = |radius: &str| match radius.parse() {
+            Ok(radius) if radius > 0 => Ok(radius),
+            _ => Err(
+                "expected a radius of at least 1, or two as <horizontal>x<vertical>".to_string(),
+            ),
+        };
+        match radius.split_once('x') {
+            Some((radius_x, radius_y)) => Ok(Self(parse(radius_x)?, parse(radius_y)?)),
+            None => parse(radius).map(|radius| Self(radius, radius)),
+        }
+    }
+}
+
+/// apply the preset of the file at `path`, keeping the current parameters when it's invalid
+fn reload_params(path: &Path, lenia: &mut Lenia) {
+    match Preset::load(path) {
+        Ok(preset) => {
+            lenia.apply_preset(&preset);
+            println!("applied the parameters of {}", path.display());
+        }
+        Err(err) => eprintln!("failed to load {}: {err}", path.display()),
+    }
+}
+
+#[derive(Default)]
+struct CliArgs {
+    /// `--rule B36/S23` switches to Game of Life with the given life-like rule
+    gol_rule: Option<GolRule>,
+    /// `--generations B2/S/3` switches to a Generations automaton with the given rule
+    generations_rule: Option<GenerationsRule>,
+    /// `--frames N` runs N frames headlessly, prints the total mass and exits
+    headless_frames: Option<usize>,
+    /// `render-frames --frames N --out frames/` runs N frames headlessly, writing the initial
+    /// grid and every frame after it to the directory as numbered PNGs
+    render_frames_dir: Option<PathBuf>,
+    /// `--neighbourhood von-neumann` changes the cells counted by the Game of Life and
+    /// Generations rules
+    neighbourhood: Option<Neighbourhood>,
+    /// `--seed-blobs 3` starts from that many random areas instead of one
+    seed_blobs: Option<usize>,
+    /// `--symmetric-seed point` starts from a symmetric soup, reseeds with one, mirrors painting
+    symmetric_seed: Option<Symmetry>,
+    /// `--update-order checkerboard` updates the Game of Life cells in two halves, see
+    /// [`UpdateOrder`]
+    update_order: Option<UpdateOrder>,
+    /// `--topology klein-bottle` joins the edges of the grid into another surface than a torus
+    topology: Option<Topology>,
+    /// `--resize-anchor center` keeps the content centered when the window is resized
+    resize_anchor: Option<ResizeAnchor>,
+    /// `--seed N` makes the initial state and every random draw reproducible
+    seed: Option<u64>,
+    /// `--render-every N` only draws every Nth frame while running, see [`Action::RenderLessOften`]
+    render_every: Option<usize>,
+    /// `--advance N` sets how many steps the advance key runs before pausing
+    advance_steps: Option<usize>,
+    /// `--kernel-radius 10x16` sets separate horizontal and vertical kernel radii
+    kernel_radius: Option<KernelRadius>,
+    /// `--adaptive-radius` scales the kernel radius with the window size
+    adaptive_kernel_radius: bool,
+    /// `--quantized-kernel` samples the kernel at distances rounded to a tenth of its radius
+    quantized_kernel: bool,
+    /// `--bind x=clear` (repeatable) rebinds a key, see [`Action::ALL`] for the action names
+    key_bindings: Vec<KeyBinding>,
+    /// `--max-frames N` halts the simulation at generation N
+    max_frames: Option<u64>,
+    /// `--exit-on-max` exits instead of halting at `--max-frames`
+    exit_on_max_frames: bool,
+    /// `--auto-pause N` halts the simulation when the grid hasn't changed for N frames
+    auto_pause_frames: Option<usize>,
+    /// `--record session.json` saves the inputs of the session on exit, see [`Replay`]
+    record: Option<PathBuf>,
+    /// `--replay session.json` replays a recorded session, with its own arguments
+    replay: Option<PathBuf>,
+    /// `--image start.png` starts from a grayscale image instead of a random soup
+    image: Option<PathBuf>,
+    /// `--image-fit letterbox` keeps the aspect ratio of `--image`
+    image_fit: ImageFit,
+    /// `--text HELLO` starts from the text stamped in the middle of an empty grid
+    text: Option<String>,
+    /// `--kernel-image kernel.png` takes the kernel weights from a square, odd-sized grayscale
+    /// image
+    kernel_image: Option<PathBuf>,
+    /// `--saturation tanh` changes how Lenia cells out of [0, 1] are brought back
+    saturation: Option<Saturation>,
+    /// `--ruler-spacing 10` sets the cells between the lines of the ruler
+    ruler_spacing: Option<usize>,
+    /// `--inner-outer-ratio 0.33` grows Lenia cells from the ratio of the inner disc (of that
+    /// fraction of the kernel radius) and outer ring averages, see [`Potential::InnerOuterRatio`]
+    inner_outer_ratio: Option<f64>,
+    /// `--target-mass 500` rescales the Lenia grid to that total mass after every step, see
+    /// [`Lenia::target_mass`]
+    target_mass: Option<f64>,
+    /// `--enforce-min-grid` clamps the kernel radius to half the grid, so that it never wraps
+    /// onto itself
+    enforce_min_grid: bool,
+    /// `--full-convolution` sums the neighbourhood of every Lenia cell, even far from any living
+    /// one, see [`Lenia::skip_empty_regions`]
+    full_convolution: bool,
+    /// `--autosave` saves the grid to [`RECOVERY_PATH`] when the window is closed (or escape is
+    /// pressed)
+    autosave: bool,
+    /// `--recover` resumes from the grid saved by `--autosave`
+    recover: bool,
+    /// `--self-check` checks the kernel tables on startup, see [`lenia::Kernel::self_check`]
+    self_check: bool,
+    /// `--stdin` reads commands from stdin, one per line, see [`Command`]
+    stdin_commands: bool,
+    /// `--watch params.toml` applies the parameters of a preset file, again whenever it changes
+    watch: Option<PathBuf>,
+}
+
+impl CliArgs {
+    fn parse(args: impl IntoIterator<Item = String>) -> Self {
+        fn fail(message: String) -> ! {
+            eprintln!("{message}");
+            std::process::exit(1);
+        }
+        fn value_of<T: FromStr>(flag: &str, value: Option<String>, example: &str) -> T
+        where
+            T::Err: std::fmt::Display,
+        {
+            let value =
+                value.unwrap_or_else(|| fail(format!("{flag} expects a value, e.g. {example}")));
+            value
+                .parse()
+                .unwrap_or_else(|err| fail(format!("invalid {flag} \"{value}\": {err}")))
+        }
+
+        let mut cli_args = Self::default();
+        let mut args = args.into_iter().peekable();
+        let is_render_frames = args.next_if_eq("render-frames").is_some();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--rule" => cli_args.gol_rule = Some(value_of(&arg, args.next(), "--rule B36/S23")),
+                "--generations" => {
+                    cli_args.generations_rule =
+                        Some(value_of(&arg, args.next(), "--generations B2/S/3"))
+                }
+                "--frames" => {
+                    cli_args.headless_frames = Some(value_of(&arg, args.next(), "--frames 100"))
+                }
+                "--neighbourhood" => {
+                    cli_args.neighbourhood =
+                        Some(value_of(&arg, args.next(), "--neighbourhood von-neumann"))
+                }
+                "--seed-blobs" => {
+                    let count = value_of(&arg, args.next(), "--seed-blobs 3");
+                    if count == 0 {
+                        fail(format!("invalid {arg} \"0\": expected at least 1 area"));
+                    }
+                    cli_args.seed_blobs = Some(count);
+                }
+                "--symmetric-seed" => {
+                    cli_args.symmetric_seed =
+                        Some(value_of(&arg, args.next(), "--symmetric-seed point"))
+                }
+                "--update-order" => {
+                    cli_args.update_order =
+                        Some(value_of(&arg, args.next(), "--update-order checkerboard"))
+                }
+                "--topology" => {
+                    cli_args.topology = Some(value_of(&arg, args.next(), "--topology klein-bottle"))
+                }
+                "--resize-anchor" => {
+                    cli_args.resize_anchor =
+                        Some(value_of(&arg, args.next(), "--resize-anchor center"))
+                }
+                "--bind" => {
+                    cli_args
+                        .key_bindings
+                        .push(value_of(&arg, args.next(), "--bind x=clear"))
+                }
+                "--seed" => cli_args.seed = Some(value_of(&arg, args.next(), "--seed 42")),
+                "--render-every" => {
+                    let frames = value_of(&arg, args.next(), "--render-every 4");
+                    if frames == 0 {
+                        fail(format!("invalid {arg} \"0\": expected at least 1 frame"));
+                    }
+                    cli_args.render_every = Some(frames);
+                }
+                "--advance" => {
+                    cli_args.advance_steps = Some(value_of(&arg, args.next(), "--advance 100"))
+                }
+                "--kernel-radius" => {
+                    cli_args.kernel_radius =
+                        Some(value_of(&arg, args.next(), "--kernel-radius 10x16"))
+                }
+                "--adaptive-radius" => cli_args.adaptive_kernel_radius = true,
+                "--quantized-kernel" => cli_args.quantized_kernel = true,
+                "--max-frames" => {
+                    cli_args.max_frames = Some(value_of(&arg, args.next(), "--max-frames 1000"))
+                }
+                "--exit-on-max" => cli_args.exit_on_max_frames = true,
+                "--auto-pause" => {
+                    let frames = value_of(&arg, args.next(), "--auto-pause 50");
+                    if frames == 0 {
+                        fail(format!("invalid {arg} \"0\": expected at least 1 frame"));
+                    }
+                    cli_args.auto_pause_frames = Some(frames);
+                }
+                "--record" => {
+                    cli_args.record = Some(value_of(&arg, args.next(), "--record session.json"))
+                }
+                "--replay" => {
+                    cli_args.replay = Some(value_of(&arg, args.next(), "--replay session.json"))
+                }
+                "--image" => {
+                    cli_args.image = Some(value_of(&arg, args.next(), "--image start.png"))
+                }
+                "--image-fit" => {
+                    cli_args.image_fit = value_of(&arg, args.next(), "--image-fit letterbox")
+                }
+                "--saturation" => {
+                    cli_args.saturation = Some(value_of(&arg, args.next(), "--saturation tanh"))
+                }
+                "--enforce-min-grid" => cli_args.enforce_min_grid = true,
+                "--full-convolution" => cli_args.full_convolution = true,
+                "--ruler-spacing" => {
+                    let spacing = value_of(&arg, args.next(), "--ruler-spacing 10");
+                    if spacing == 0 {
+                        fail(format!("invalid {arg} \"0\": expected at least 1 cell"));
+                    }
+                    cli_args.ruler_spacing = Some(spacing);
+                }
+                "--inner-outer-ratio" => {
+                    let inner_fraction = value_of(&arg, args.next(), "--inner-outer-ratio 0.33");
+                    if !(0.0..1.0).contains(&inner_fraction) || inner_fraction == 0.0 {
+                        fail(format!(
+                            "invalid {arg} \"{inner_fraction}\": expected a fraction of the \
+                             kernel radius between 0 and 1"
+                        ));
+                    }
+                    cli_args.inner_outer_ratio = Some(inner_fraction);
+                }
+                "--target-mass" => {
+                    let mass: f64 = value_of(&arg, args.next(), "--target-mass 500");
+                    if mass.is_nan() || mass <= 0.0 {
+                        fail(format!(
+                            "invalid {arg} \"{mass}\": expected a positive mass"
+                        ));
+                    }
+                    cli_args.target_mass = Some(mass);
+                }
+                "--text" => cli_args.text = Some(value_of(&arg, args.next(), "--text HELLO")),
+                "--kernel-image" => {
+                    cli_args.kernel_image =
+                        Some(value_of(&arg, args.next(), "--kernel-image kernel.png"))
+                }
+                "--stdin" => cli_args.stdin_commands = true,
+                "--self-check" => cli_args.self_check = true,
+                "--autosave" => cli_args.autosave = true,
+                "--recover" => cli_args.recover = true,
+                "--watch" => {
+                    cli_args.watch = Some(value_of(&arg, args.next(), "--watch params.toml"))
+                }
+                "--out" if is_render_frames => {
+                    cli_args.render_frames_dir = Some(value_of(&arg, args.next(), "--out frames/"))
+                }
+                _ => fail(format!("unknown argument: {arg}")),
+            }
+        }
+        if is_render_frames
+            && (cli_args.headless_frames.is_none() || cli_args.render_frames_dir.is_none())
+        {
+            fail("render-frames expects --frames N and --out <directory>".to_string());
+        }
+        cli_args
+    }
+}
+
+/// draw the grid as the last update left it on a render event, or only count the frame in
+/// benchmark mode
+fn draw_frame(
+    event: &Event,
+    window: &mut PistonWindow,
+    app_state: &mut AppState,
+    lenia: &Lenia,
+    smooth_renderer: &mut SmoothRenderer,
+) {
+    if let Some(benchmark) = &mut app_state.benchmark {
+        benchmark.frames += 1;
+        let elapsed = benchmark.since.elapsed();
+        if elapsed >= Duration::from_secs(1) {
+            let seconds = elapsed.as_secs_f64();
+            println!(
+                "{:.1} frames/s, {:.1} steps/s",
+                benchmark.frames as f64 / seconds,
+                // an undo can take the generation back below the start of the second
+                lenia.generation.saturating_sub(benchmark.generation) as f64 / seconds,
+            );
+            *benchmark = Benchmark {
+                since: Instant::now(),
+                frames: 0,
+                generation: lenia.generation,
+            };
+            // the grid isn't drawn, but the window shouldn't show a stale one either
+            window.draw_2d(event, |_context, graphics, _device| {
+                clear(app_state.theme.background_color(), graphics);
+            });
+            Window::swap_buffers(window);
+        }
+    } else if app_state.take_render() {
+        let title = hud(app_state, lenia);
+        if title != window.get_title() {
+            window.set_title(title);
+        }
+        if app_state.is_render_smooth && !app_state.show_kernel {
+            if let Err(err) = smooth_renderer.update(lenia, app_state) {
+                eprintln!("failed to upload the cells for smooth rendering: {err}");
+                app_state.is_render_smooth = false;
+            }
+        }
+        window.draw_2d(event, |context, graphics, device| {
+            smooth_renderer.texture_context.encoder.flush(device);
+            clear(app_state.theme.background_color(), graphics);
+            if app_state.show_kernel {
+                render_kernel(lenia, app_state, context, graphics);
+            } else {
+                let smooth = app_state.is_render_smooth.then_some(&*smooth_renderer);
+                render(lenia, app_state, smooth, context, graphics);
+                if app_state.show_ruler {
+                    render_ruler(lenia, app_state, context, graphics);
+                }
+            }
+            if app_state.show_help {
+                render_help(app_state, context, graphics);
+            }
+        });
+        Window::swap_buffers(window);
+    }
+}
+
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut cli_args = CliArgs::parse(args.clone());
+    let mut replay = None;
+    if let Some(path) = &cli_args.replay {
+        match Replay::load(path) {
+            Ok(loaded) => {
+                cli_args = CliArgs::parse(loaded.args.clone());
+                cli_args.seed = Some(loaded.seed);
+                replay = Some(loaded);
+            }
+            Err(err) => {
+                eprintln!("failed to load {}: {err}", path.display());
+                std::process::exit(1);
+            }
+        }
+    }
+    // a recording needs a seed, so that replays draw the same random values
+    let mut recording = cli_args.record.clone().map(|path| {
+        let seed = *cli_args.seed.get_or_insert_with(|| fastrand::u64(..));
+        let mut args = args.into_iter();
+        let mut recorded_args = vec![];
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--record" => _ = args.next(),
+                _ => recorded_args.push(arg),
+            }
+        }
+        let replay = Replay {
+            args: recorded_args,
+            seed,
+            events: vec![],
+        };
+        (path, replay)
+    });
+    if let Some(seed) = cli_args.seed {
+        fastrand::seed(seed);
+    }
+
     let mut lenia = Lenia::new(
         (DEFAULT_WCELL_COUNT, DEFAULT_HCELL_COUNT),
-        generate_spawn_area(DEFAULT_WCELL_COUNT, DEFAULT_HCELL_COUNT),
-        Some(Mode::Lenia),
+        None,
+        Some(
+            match (&cli_args.gol_rule, cli_args.generations_rule.clone()) {
+                (Some(_), _) => Mode::GameOfLife,
+                (None, Some(rule)) => Mode::Generations(rule),
+                (None, None) => Mode::Lenia,
+            },
+        ),
         None,
         None,
     );
-    let mut window: PistonWindow = WindowSettings::new("Lenia!", [DEFAULT_WIDTH, DEFAULT_HEIGHT])
-        .build()
-        .unwrap();
+    if let Some(rule) = cli_args.gol_rule {
+        lenia.gol_rule = rule;
+    }
+    if let Some(neighbourhood) = cli_args.neighbourhood {
+        lenia.neighbourhood = neighbourhood;
+    }
+    if let Some(update_order) = cli_args.update_order {
+        lenia.update_order = update_order;
+    }
+    if let Some(topology) = cli_args.topology {
+        lenia.boundary = topology.boundary();
+    }
+    if let Some(anchor) = cli_args.resize_anchor {
+        lenia.resize_anchor = anchor;
+    }
+    lenia.is_kernel_quantized = cli_args.quantized_kernel;
+    if let Some(KernelRadius(radius_x, radius_y)) = cli_args.kernel_radius {
+        lenia.kernel_radius = (radius_x, radius_y);
+    }
+    if cli_args.adaptive_kernel_radius {
+        let adaptive_kernel_radius = AdaptiveKernelRadius::default();
+        let radius = adaptive_kernel_radius.radius_for(DEFAULT_WCELL_COUNT, DEFAULT_HCELL_COUNT);
+        lenia.kernel_radius = (radius, radius);
+        lenia.adaptive_kernel_radius = Some(adaptive_kernel_radius);
+    }
+
+    if let Some(count) = cli_args.seed_blobs {
+        lenia.seed_blobs(count);
+    }
+    if let Some(symmetry) = cli_args.symmetric_seed {
+        lenia.seed_symmetric(symmetry);
+    }
+    if let Some(text) = &cli_args.text {
+        lenia.clear();
+        let (text_w, text_h) = text_size(text);
+        lenia.stamp_text(
+            text,
+            (
+                DEFAULT_HCELL_COUNT.saturating_sub(text_h) / 2,
+                DEFAULT_WCELL_COUNT.saturating_sub(text_w) / 2,
+            ),
+        );
+    }
+    if let Some(path) = &cli_args.kernel_image {
+        #[cfg(feature = "image")]
+        if let Err(err) = lenia.load_kernel_image(path) {
+            eprintln!("failed to load {}: {err}", path.display());
+            std::process::exit(1);
+        }
+        #[cfg(not(feature = "image"))]
+        eprintln!("loading {} requires the \"image\" feature", path.display());
+    }
+    if let Some(saturation) = cli_args.saturation {
+        lenia.saturation = saturation;
+    }
+    if let Some(inner_fraction) = cli_args.inner_outer_ratio {
+        lenia.potential = Potential::InnerOuterRatio { inner_fraction };
+    }
+    lenia.target_mass = cli_args.target_mass;
+    lenia.enforce_min_grid = cli_args.enforce_min_grid;
+    lenia.skip_empty_regions = !cli_args.full_convolution;
+    if lenia.enforce_min_grid && lenia.clamp_kernel_radius() {
+        println!(
+            "kernel radius clamped to {:?} to fit the grid",
+            lenia.kernel_radius
+        );
+    }
+
+    if let Some(path) = &cli_args.image {
+        #[cfg(feature = "image")]
+        if let Err(err) = lenia.load_image(path, cli_args.image_fit) {
+            eprintln!("failed to load {}: {err}", path.display());
+            std::process::exit(1);
+        }
+        #[cfg(not(feature = "image"))]
+        eprintln!("loading {} requires the \"image\" feature", path.display());
+    }
+
+    if cli_args.recover {
+        match lenia.load_state(RECOVERY_PATH.as_ref()) {
+            Ok(()) => println!(
+                "recovered the grid from {RECOVERY_PATH}, at generation {}",
+                lenia.generation
+            ),
+            Err(err) => {
+                eprintln!("failed to load {RECOVERY_PATH}: {err}");
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let params_watcher = cli_args.watch.as_deref().and_then(|path| {
+        reload_params(path, &mut lenia);
+        ParamsWatcher::new(path)
+            .inspect_err(|err| eprintln!("failed to watch {}: {err}", path.display()))
+            .ok()
+    });
+
+    if cli_args.self_check {
+        let kernels = lenia.sim_params().kernels;
+        for (index, spec) in kernels.iter().enumerate() {
+            if let Err(err) = spec.table.self_check() {
+                eprintln!("self-check failed for kernel {index}: {err}");
+                std::process::exit(1);
+            }
+        }
+        println!(
+            "self-check passed: {} kernel table(s) summing to 1 and symmetric",
+            kernels.len()
+        );
+    }
+    if let (Some(frames), Some(dir)) = (cli_args.headless_frames, &cli_args.render_frames_dir) {
+        #[cfg(feature = "image")]
+        match render_frames(&mut lenia, frames, dir) {
+            Ok(()) => println!("wrote {} frames to {}", frames + 1, dir.display()),
+            Err(err) => {
+                eprintln!("failed to write the frames to {}: {err}", dir.display());
+                std::process::exit(1);
+            }
+        }
+        #[cfg(not(feature = "image"))]
+        eprintln!(
+            "rendering {frames} frames to {} requires the \"image\" feature",
+            dir.display()
+        );
+        return;
+    }
+    if let Some(frames) = cli_args.headless_frames {
+        lenia.run(frames);
+        println!("total mass after {frames} frames: {}", lenia.total_mass());
+        return;
+    }
+
+    let mut window = build_window().unwrap_or_else(|err| {
+        eprintln!("failed to open the window: {err}");
+        eprintln!(
+            "the window needs a display (X11 or Wayland, check $DISPLAY) and an OpenGL 3.2 \
+             capable GPU driver; without them, run headless with --frames N"
+        );
+        std::process::exit(1);
+    });
     // window.set_max_fps(12);
+    // buffers are swapped manually, only on frames that are actually drawn
+    window.set_swap_buffers(false);
+    // as many updates as drawn frames (60/s), each computing `compute_per_render` steps
+    window.set_ups(60);
+    let mut smooth_renderer = SmoothRenderer::new(&mut window);
+
+    let mut keymap = Keymap::default();
+    for KeyBinding(character, action) in cli_args.key_bindings {
+        keymap.bind(character, action);
+    }
 
-    let mut app_state = AppState::default();
+    let mut app_state = AppState {
+        help_lines: keymap.help_lines(),
+        ..AppState::default()
+    };
+    match Preset::load_all(PRESETS_PATH.as_ref()) {
+        Ok(presets) => app_state.presets = presets,
+        Err(err) => eprintln!("failed to load {PRESETS_PATH}: {err}"),
+    }
+    if let Some(steps) = cli_args.advance_steps {
+        app_state.advance_steps = steps;
+    }
+    if let Some(frames) = cli_args.render_every {
+        app_state.render_every = frames;
+    }
+    if let Some(spacing) = cli_args.ruler_spacing {
+        app_state.ruler_spacing = spacing;
+    }
+    app_state.max_frames = cli_args.max_frames;
+    app_state.seed_symmetry = cli_args.symmetric_seed;
+    app_state.auto_pause_frames = cli_args.auto_pause_frames;
+    // update events, the clock of recordings and replays
+    let mut frame = 0;
+    let commands = cli_args.stdin_commands.then(command::spawn_stdin_reader);
+    // live inputs and window resizes are ignored until the replay is over
+    let mut replay_events = replay.map(|replay| replay.events.into_iter().peekable());
     while let Some(event) = window.next() {
+        // the commands after `step N` wait for its steps, so that scripts run in order
+        while let Some(command) = commands
+            .as_ref()
+            .filter(|_| app_state.pending_steps.is_none())
+            .and_then(|commands| commands.try_recv().ok())
+        {
+            apply_command(command, &mut app_state, &mut lenia);
+        }
         let Size { width, height } = window.size();
         let (wcell_count, hcell_count) = (
             (width / CELL_DIMENSION) as usize,
             (height / CELL_DIMENSION) as usize,
         );
-        lenia.resize(wcell_count, hcell_count);
-
-        if let Event::Input(input, _) = &event {
-            match input {
-                Input::Move(Motion::MouseCursor([x, y])) => {
-                    if app_state.is_drawing {
-                        let (raw, col) = (
-                            (y / CELL_DIMENSION).floor() as usize,
-                            (x / CELL_DIMENSION).floor() as usize,
-                        );
-                        lenia.cells[raw][col] = if app_state.is_erasing { 0.0 } else { 1.0 };
-                    }
-                }
-                Input::Text(text) => {
-                    let character = text.chars().next().unwrap(); // cannot panic
-                    match character {
-                        'r' => {
-                            let ((area_w_min, area_w_max), (area_h_min, area_h_max)) =
-                                generate_spawn_area(wcell_count, hcell_count);
-                            for raw in lenia.cells.iter_mut().take(area_h_max + 1).skip(area_h_min)
-                            {
-                                for cell in raw.iter_mut().take(area_w_max + 1).skip(area_w_min) {
-                                    *cell = match lenia.mode {
-                                        Mode::Lenia => fastrand::f32(),
-                                        Mode::GameOfLife => fastrand::usize(0..=1) as f32,
-                                    }
-                                }
-                            }
-                        }
-                        'c' => {
-                            lenia.cells = vec![vec![0_f32; lenia.cells[0].len()]; lenia.cells.len()]
-                        }
-                        's' => {
-                            app_state.is_game_paused = false;
-                            app_state.is_drawing = false;
-                        }
-                        'h' => app_state.is_game_paused = true,
-                        'd' => {
-                            app_state.is_drawing = !app_state.is_drawing;
-                            app_state.is_game_paused = true;
-                        }
-                        _ => (),
-                    }
+        // a minimized window has no room for the grid, which keeps its size and waits
+        let is_window_usable = wcell_count >= MIN_GRID_SIZE && hcell_count >= MIN_GRID_SIZE;
+        let grid_size = (lenia.cells[0].len(), lenia.cells.len());
+        if is_window_usable && replay_events.is_none() && grid_size != (wcell_count, hcell_count) {
+            lenia.resize(wcell_count, hcell_count);
+            if lenia.enforce_min_grid && lenia.clamp_kernel_radius() {
+                println!(
+                    "kernel radius clamped to {:?} to fit the grid",
+                    lenia.kernel_radius
+                );
+            }
+            if let Some((_, recording)) = &mut recording {
+                recording.events.push(ReplayEvent {
+                    frame,
+                    kind: ReplayEventKind::GridSize(wcell_count, hcell_count),
+                });
+            }
+        }
+
+        if let (Event::Input(input, _), None) = (&event, &replay_events) {
+            if let Some((_, recording)) = &mut recording {
+                recording.events.push(ReplayEvent {
+                    frame,
+                    kind: ReplayEventKind::Input(input.clone()),
+                });
+            }
+            let request = handle_input(input, &mut app_state, &mut lenia, &keymap);
+            apply_window_request(request, &mut window);
+        }
+
+        if !is_window_usable {
+            continue;
+        }
+        let Event::Loop(loop_event) = &event else {
+            continue;
+        };
+        if let Loop::Update(_) = loop_event {
+            if let Some(events) = &mut replay_events {
+                while let Some(replayed) = events.next_if(|replayed| replayed.frame <= frame) {
+                    let request =
+                        handle_replay_event(&replayed.kind, &mut app_state, &mut lenia, &keymap);
+                    apply_window_request(request, &mut window);
                 }
-                Input::Button(button_action) => {
-                    if app_state.is_drawing {
-                        if let Button::Mouse(mouse_action) = button_action.button {
-                            match mouse_action {
-                                MouseButton::Left => app_state.is_erasing = false,
-                                MouseButton::Right => app_state.is_erasing = true,
-                                _ => (),
-                            }
-                        }
-                    }
+                if events.peek().is_none() {
+                    println!("replay over at frame {frame}");
+                    replay_events = None;
                 }
-                _ => (),
             }
+            if let Some(watcher) = params_watcher
+                .as_ref()
+                .filter(|watcher| watcher.has_changed())
+            {
+                reload_params(watcher.path(), &mut lenia);
+            }
+            frame += 1;
+        }
+        match handle_loop(loop_event, &mut app_state, &mut lenia) {
+            LoopOutcome::Draw => draw_frame(
+                &event,
+                &mut window,
+                &mut app_state,
+                &lenia,
+                &mut smooth_renderer,
+            ),
+            LoopOutcome::ReachedMaxFrames if cli_args.exit_on_max_frames => break,
+            LoopOutcome::ReachedMaxFrames | LoopOutcome::Idle => (),
+        }
+    }
+
+    if cli_args.autosave {
+        match lenia.save_state(RECOVERY_PATH.as_ref()) {
+            Ok(()) => println!("saved the grid to {RECOVERY_PATH}, --recover loads it"),
+            Err(err) => eprintln!("failed to save {RECOVERY_PATH}: {err}"),
+        }
+    }
+    if let Some((path, recording)) = recording {
+        match recording.save(&path) {
+            Ok(()) => println!("saved the session to {}", path.display()),
+            Err(err) => eprintln!("failed to save {}: {err}", path.display()),
         }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        window.draw_2d(&event, |context, graphics, _device| {
-            clear([0.0, 0.0, 0.0, 1.0], graphics);
-            if !app_state.is_game_paused {
-                lenia.compute_next_frame();
+    #[test]
+    fn cells_are_opaque_from_the_threshold_up() {
+        let cutoff = 0.5;
+        assert_eq!(cell_alpha(cutoff, Some(cutoff)), 1.0);
+        assert_eq!(cell_alpha(cutoff - 0.01, Some(cutoff)), 0.0);
+        assert_eq!(cell_alpha(cutoff + 0.01, Some(cutoff)), 1.0);
+        assert_eq!(cell_alpha(0.0, Some(0.0)), 1.0);
+        // without a threshold, the opacity is the cell itself
+        for cell in [0.0, 0.25, 0.5, 1.0] {
+            assert_eq!(cell_alpha(cell, None), cell as f32);
+        }
+    }
+
+    #[test]
+    fn set_commands_clamp_the_kernel_radius_to_the_grid() {
+        let mut app_state = AppState::default();
+        let mut lenia = Lenia::builder().dimensions(32, 24).seed(1).build().unwrap();
+        let max_radius = lenia.max_kernel_radius();
+        for (radius, expected) in [(100, max_radius), (4, 4)] {
+            let command = Command::Set(Parameter::KernelRadius, radius as f64);
+            apply_command(command, &mut app_state, &mut lenia);
+            assert_eq!(lenia.kernel_radius, (expected, expected));
+        }
+        apply_command(Command::Set(Parameter::Mu, 0.3), &mut app_state, &mut lenia);
+        assert_eq!(lenia.kernels[0].mu, 0.3);
+    }
+
+    #[test]
+    fn wheel_deltas_add_up_to_whole_steps_within_the_bounds() {
+        let mut app_state = AppState {
+            tuned_parameter: TunedParameter::KernelRadius,
+            ..AppState::default()
+        };
+        let mut lenia = Lenia::builder()
+            .dimensions(32, 32)
+            .kernel_radius(10)
+            .seed(1)
+            .build()
+            .unwrap();
+        // touchpads scroll by fractions of a notch
+        for (dy, expected) in [(0.5, 10), (0.75, 11), (-0.5, 11), (-0.75, 10)] {
+            app_state.scroll(&mut lenia, dy);
+            assert_eq!(lenia.kernel_radius, (expected, expected), "{dy}");
+        }
+        app_state.scroll(&mut lenia, 100.0);
+        assert_eq!(lenia.kernel_radius, (15, 15));
+        app_state.scroll(&mut lenia, -100.0);
+        assert_eq!(lenia.kernel_radius, (1, 1));
+
+        app_state.tuned_parameter = TunedParameter::BrushRadius;
+        app_state.brush_radius = 1;
+        app_state.scroll(&mut lenia, -3.0);
+        assert_eq!(app_state.brush_radius, 0);
+        app_state.scroll(&mut lenia, 100.0);
+        assert_eq!(app_state.brush_radius, MAX_BRUSH_RADIUS);
+    }
+
+    #[test]
+    fn running_draws_every_nth_frame_and_halted_every_one() {
+        let mut app_state = AppState {
+            render_every: 3,
+            is_game_paused: false,
+            ..AppState::default()
+        };
+        let mut drawn = Vec::new();
+        for frame in 1..=7 {
+            // each running frame computes a step first
+            app_state.frames_since_render += 1;
+            if app_state.take_render() {
+                drawn.push(frame);
             }
-            lenia.render(context, graphics);
-        });
+        }
+        assert_eq!(drawn, [3, 6]);
+        app_state.is_game_paused = true;
+        assert!(app_state.take_render());
+        assert!(app_state.take_render());
+        assert_eq!(app_state.frames_since_render, 0);
+    }
+
+    #[test]
+    fn ruler_lines_fall_every_spacing_cells_inside_the_grid() {
+        let cells = |spacing, cell_count| {
+            let lines = ruler_lines(spacing, cell_count).map(|(cell, _)| cell);
+            lines.collect::<Vec<_>>()
+        };
+        assert_eq!(cells(10, 40), [10, 20, 30]);
+        // not a multiple of the spacing: the last line is within the grid
+        assert_eq!(cells(10, 45), [10, 20, 30, 40]);
+        assert_eq!(cells(10, 10), Vec::<usize>::new());
+        assert_eq!(cells(16, 8), Vec::<usize>::new());
+        // a 0 spacing is taken as 1
+        assert_eq!(cells(0, 4), [1, 2, 3]);
+
+        let offsets: Vec<_> = ruler_lines(8, 20).collect();
+        assert_eq!(
+            offsets,
+            [(8, 8.0 * CELL_DIMENSION), (16, 16.0 * CELL_DIMENSION)]
+        );
     }
 }
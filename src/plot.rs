@@ -0,0 +1,104 @@
+//! PNG plots of the Lenia kernel profile and growth curve, to help tuning parameters
+
+use crate::{kernel_core_function, Lenia};
+use plotters::prelude::*;
+use std::{error::Error, ops::Range, path::Path};
+
+const PLOT_SIZE: (u32, u32) = (640, 360);
+
+impl Lenia {
+    /// weight (before normalization) of the first kernel over the horizontal distance from the cell,
+    /// 0 to its horizontal radius
+    pub fn plot_kernel(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let spec = &self.kernels[0];
+        let (radius, _) = spec.radius(self.kernel_radius);
+        plot_curve(
+            path,
+            (0..=radius).map(|distance| {
+                (
+                    distance as f64,
+                    kernel_core_function(
+                        distance as f64 / radius as f64,
+                        &spec.peaks,
+                        self.is_kernel_quantized,
+                        self.kernel_alpha,
+                    ),
+                )
+            }),
+            0.0..radius as f64,
+            0.0..1.0,
+        )
+    }
+
+    /// growth of the first kernel over the potential distribution, 0 to 1
+    pub fn plot_growth(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        const SAMPLES: usize = 500;
+        let spec = &self.kernels[0];
+        plot_curve(
+            path,
+            (0..=SAMPLES).map(|sample| {
+                let potential_distribution = sample as f64 / SAMPLES as f64;
+                (potential_distribution, spec.growth(potential_distribution))
+            }),
+            0.0..1.0,
+            -1.0..1.0,
+        )
+    }
+}
+
+/// line plot over a light grid, without any text (plotters is built without a font backend)
+fn plot_curve(
+    path: &Path,
+    points: impl IntoIterator<Item = (f64, f64)>,
+    x_range: Range<f64>,
+    y_range: Range<f64>,
+) -> Result<(), Box<dyn Error>> {
+    let root = BitMapBackend::new(path, PLOT_SIZE).into_drawing_area();
+    root.fill(&WHITE)?;
+    let mut chart = ChartBuilder::on(&root)
+        .margin(16)
+        .build_cartesian_2d(x_range.clone(), y_range)?;
+    chart
+        .configure_mesh()
+        .x_labels(10)
+        .y_labels(10)
+        .x_label_formatter(&|_| String::new())
+        .y_label_formatter(&|_| String::new())
+        .draw()?;
+    // y = 0
+    chart.draw_series(LineSeries::new(
+        [(x_range.start, 0.0), (x_range.end, 0.0)],
+        &BLACK,
+    ))?;
+    chart.draw_series(LineSeries::new(points, RED.stroke_width(2)))?;
+    root.present()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plots_are_written_as_png_files() {
+        let lenia = Lenia::builder().dimensions(32, 32).seed(1).build().unwrap();
+        let dir = std::env::temp_dir().join(format!("lenia-plots-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let (kernel_path, growth_path) = (dir.join("kernel.png"), dir.join("growth.png"));
+        let plotted = (
+            lenia.plot_kernel(&kernel_path),
+            lenia.plot_growth(&growth_path),
+        );
+        let files = [&kernel_path, &growth_path].map(|path| std::fs::read(path).unwrap());
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(plotted.0.is_ok() && plotted.1.is_ok());
+        for png in files {
+            assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+            // the IHDR chunk leads with the width and height
+            assert_eq!(&png[12..16], b"IHDR");
+            assert_eq!(png[16..20], PLOT_SIZE.0.to_be_bytes());
+            assert_eq!(png[20..24], PLOT_SIZE.1.to_be_bytes());
+        }
+    }
+}
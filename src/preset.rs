@@ -0,0 +1,70 @@
+//! named sets of Lenia parameters, stored in a TOML file as `[[preset]]` tables
+
+use crate::{KernelSpec, Lenia};
+use serde::{Deserialize, Serialize};
+use std::{error::Error, path::Path};
+
+/// the tuning of a Lenia instance, without its cells
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Preset {
+    /// optional in a single preset file, see [`Preset::load`]
+    #[serde(default)]
+    pub name: String,
+    pub delta_t: f64,
+    /// (horizontal, vertical) in cells width
+    pub kernel_radius: (usize, usize),
+    pub kernel_alpha: f64,
+    pub kernels: Vec<KernelSpec>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PresetsFile {
+    #[serde(default)]
+    preset: Vec<Preset>,
+}
+
+impl Preset {
+    pub fn from_lenia(name: String, lenia: &Lenia) -> Self {
+        Self {
+            name,
+            delta_t: lenia.delta_t,
+            kernel_radius: lenia.kernel_radius,
+            kernel_alpha: lenia.kernel_alpha,
+            kernels: lenia.kernels.clone(),
+        }
+    }
+
+    /// every preset of the file at `path`, none when it doesn't exist
+    pub fn load_all(path: &Path) -> Result<Vec<Self>, Box<dyn Error>> {
+        if !path.exists() {
+            return Ok(vec![]);
+        }
+        let file: PresetsFile = toml::from_str(&std::fs::read_to_string(path)?)?;
+        Ok(file.preset)
+    }
+
+    /// the single preset of the file at `path`, written at the top level rather than as a
+    /// `[[preset]]` table
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        Ok(toml::from_str(&std::fs::read_to_string(path)?)?)
+    }
+
+    /// overwrite the file at `path` with `presets`
+    pub fn save_all(path: &Path, presets: &[Self]) -> Result<(), Box<dyn Error>> {
+        let file = PresetsFile {
+            preset: presets.to_vec(),
+        };
+        std::fs::write(path, toml::to_string_pretty(&file)?)?;
+        Ok(())
+    }
+}
+
+impl Lenia {
+    /// take the parameters of `preset`, the kernels being rebuilt on the next step
+    pub fn apply_preset(&mut self, preset: &Preset) {
+        self.delta_t = preset.delta_t;
+        self.kernel_radius = preset.kernel_radius;
+        self.kernel_alpha = preset.kernel_alpha;
+        self.kernels = preset.kernels.clone();
+    }
+}
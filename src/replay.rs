@@ -0,0 +1,121 @@
+//! Session recordings, replaying the same inputs on the same frames to reproduce a session
+
+use piston_window::Input;
+use serde::{Deserialize, Serialize};
+use std::{error::Error, path::Path};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Replay {
+    /// command line arguments of the recorded session, without `--record`
+    pub args: Vec<String>,
+    /// seed of the random generator, so that seeding and noise draw the same values
+    pub seed: u64,
+    pub events: Vec<ReplayEvent>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayEvent {
+    /// number of frames rendered before the event, which is what makes a replay deterministic
+    /// where wall-clock time wouldn't be
+    pub frame: u64,
+    pub kind: ReplayEventKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ReplayEventKind {
+    Input(Input),
+    /// the window was resized to this many (width, height) cells
+    GridSize(usize, usize),
+}
+
+impl Replay {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        std::fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        events::{handle_input, handle_loop, handle_replay_event},
+        keymap::Keymap,
+        AppState,
+    };
+    use lenia::{Lenia, Mode};
+    use piston_window::{Loop, UpdateArgs};
+
+    const UPDATE: Loop = Loop::Update(UpdateArgs { dt: 1.0 / 60.0 });
+
+    fn session_start(seed: u64) -> (AppState, Lenia) {
+        // what `fastrand::seed` does for a session, without the global generator the tests share
+        let lenia = Lenia::builder()
+            .dimensions(32, 32)
+            .mode(Mode::GameOfLife)
+            .seed(seed)
+            .build()
+            .unwrap();
+        (AppState::default(), lenia)
+    }
+
+    #[test]
+    fn replaying_a_recording_reproduces_the_session() {
+        let keymap = Keymap::default();
+        let text = |text: &str| Input::Text(text.to_string());
+        // (frame, event) of a scripted session: run, invert, grow the window, reseed, halt
+        let script = [
+            (0, ReplayEventKind::Input(text("s"))),
+            (2, ReplayEventKind::Input(text("i"))),
+            (4, ReplayEventKind::GridSize(40, 36)),
+            (6, ReplayEventKind::Input(text("r"))),
+            (10, ReplayEventKind::Input(text("h"))),
+        ];
+
+        let (mut app_state, mut lenia) = session_start(7);
+        let mut recording = Replay {
+            args: vec![],
+            seed: 7,
+            events: vec![],
+        };
+        for frame in 0..16 {
+            for (_, kind) in script.iter().filter(|(at, _)| *at == frame) {
+                match kind {
+                    ReplayEventKind::Input(input) => {
+                        handle_input(input, &mut app_state, &mut lenia, &keymap);
+                    }
+                    ReplayEventKind::GridSize(w, h) => lenia.resize(*w, *h),
+                }
+                recording.events.push(ReplayEvent {
+                    frame,
+                    kind: kind.clone(),
+                });
+            }
+            handle_loop(&UPDATE, &mut app_state, &mut lenia);
+        }
+
+        let path = std::env::temp_dir().join(format!("lenia-replay-{}.json", std::process::id()));
+        recording.save(&path).unwrap();
+        let loaded = Replay::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        let loaded = loaded.unwrap();
+        assert_eq!(loaded, recording);
+
+        let (mut replayed_state, mut replayed) = session_start(loaded.seed);
+        let mut events = loaded.events.into_iter().peekable();
+        for frame in 0..16 {
+            while let Some(event) = events.next_if(|event| event.frame <= frame) {
+                handle_replay_event(&event.kind, &mut replayed_state, &mut replayed, &keymap);
+            }
+            handle_loop(&UPDATE, &mut replayed_state, &mut replayed);
+        }
+        assert_eq!(replayed.generation, lenia.generation);
+        assert_eq!(replayed.cells, lenia.cells);
+        assert!(replayed.cells.iter().flatten().any(|&cell| cell == 1.0));
+        assert!(replayed_state.is_game_paused);
+    }
+}
@@ -0,0 +1,410 @@
+//! Game of Life patterns in the RLE format of Golly and the LifeWiki, and with the `macrocell`
+//! feature in Golly's macrocell (`.mc`) format, a quadtree for very large sparse patterns
+
+use crate::{Lenia, Mode, Neighbourhood};
+use std::path::Path;
+
+/// RLE lines are wrapped before this many characters, as Golly writes them
+const RLE_LINE_LENGTH: usize = 70;
+
+/// a pattern read from RLE, see [`parse_rle`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RlePattern {
+    /// (width, height) from the `x = ..., y = ...` header
+    pub size: (usize, usize),
+    /// from the `rule = ...` header, e.g. `B3/S23`
+    pub rule: Option<String>,
+    /// (x, y) of the top-left corner in Golly's coordinates, from a `#CXRLE Pos=x,y` line
+    pub position: Option<(isize, isize)>,
+    /// (raw, col) of every live cell from the top-left corner, raw by raw
+    pub cells: Vec<(usize, usize)>,
+}
+
+/// read a two-state RLE pattern: `#` comment lines (of which only Golly's `#CXRLE Pos=x,y` is
+/// kept), the `x = <width>, y = <height>[, rule = <rule>]` header, then runs of dead (`b` or `.`)
+/// and live (`o` or `A`) cells and of raw ends (`$`) up to `!`; whitespace and line breaks
+/// between the runs are ignored
+pub fn parse_rle(text: &str) -> Result<RlePattern, String> {
+    let mut pattern = RlePattern::default();
+    let mut lines = text.lines().map(str::trim).filter(|line| !line.is_empty());
+    let header = loop {
+        match lines.next() {
+            Some(line) if line.starts_with('#') => {
+                if let Some(extended) = line.strip_prefix("#CXRLE") {
+                    pattern.position = parse_xrle_position(extended)?;
+                }
+            }
+            Some(line) => break line,
+            None => return Err("no \"x = <width>, y = <height>\" header".to_string()),
+        }
+    };
+    let (mut width, mut height) = (None, None);
+    for field in header.split(',') {
+        let (key, value) = field
+            .split_once('=')
+            .ok_or_else(|| format!("expected <key> = <value> in the header, got \"{field}\""))?;
+        let size = || {
+            value
+                .trim()
+                .parse::<usize>()
+                .map_err(|err| format!("invalid {} \"{}\": {err}", key.trim(), value.trim()))
+        };
+        match key.trim() {
+            "x" => width = Some(size()?),
+            "y" => height = Some(size()?),
+            "rule" => pattern.rule = Some(value.trim().to_string()),
+            // other fields, e.g. Golly's unofficial ones, don't change the cells
+            _ => {}
+        }
+    }
+    pattern.size = match (width, height) {
+        (Some(width), Some(height)) => (width, height),
+        _ => return Err(format!("the header \"{header}\" lacks x or y")),
+    };
+
+    let (mut raw, mut col) = (0, 0);
+    let mut count: Option<usize> = None;
+    'runs: for line in lines {
+        for character in line.chars() {
+            if let Some(digit) = character.to_digit(10) {
+                count = count
+                    .unwrap_or(0)
+                    .checked_mul(10)
+                    .and_then(|count| count.checked_add(digit as usize));
+                if count.is_none() {
+                    return Err("a run count overflows".to_string());
+                }
+                continue;
+            }
+            if character.is_whitespace() {
+                continue;
+            }
+            let run = count.take().unwrap_or(1);
+            match character {
+                'b' | '.' => col += run,
+                'o' | 'A' => {
+                    pattern.cells.extend((col..col + run).map(|col| (raw, col)));
+                    col += run;
+                }
+                '$' => (raw, col) = (raw + run, 0),
+                '!' => break 'runs,
+                character => return Err(format!("unexpected '{character}' in the cells")),
+            }
+        }
+    }
+    Ok(pattern)
+}
+
+/// (x, y) of a `#CXRLE` line, e.g. ` Pos=-12,30 Gen=4`, `None` without a position
+fn parse_xrle_position(extended: &str) -> Result<Option<(isize, isize)>, String> {
+    let Some(position) = extended
+        .split_whitespace()
+        .find_map(|field| field.strip_prefix("Pos="))
+    else {
+        return Ok(None);
+    };
+    let invalid = || format!("invalid #CXRLE Pos=\"{position}\": expected Pos=<x>,<y>");
+    let (x, y) = position.split_once(',').ok_or_else(invalid)?;
+    Ok(Some((
+        x.parse().map_err(|_| invalid())?,
+        y.parse().map_err(|_| invalid())?,
+    )))
+}
+
+impl Lenia {
+    /// (raw, col) of every live cell, raw by raw: at least 0.5 as when switching to Game of
+    /// Life, only the live state of a Generations automaton and not its dying ones
+    pub(crate) fn live_cells(&self) -> Vec<(usize, usize)> {
+        let threshold = match self.mode {
+            Mode::Generations(_) => 1.0,
+            _ => 0.5,
+        };
+        self.cells
+            .iter()
+            .enumerate()
+            .flat_map(|(raw, cells)| {
+                cells
+                    .iter()
+                    .enumerate()
+                    .filter(move |&(_, &cell)| cell >= threshold)
+                    .map(move |(col, _)| (raw, col))
+            })
+            .collect()
+    }
+
+    /// the rule in Golly's notation, none in Lenia or for neighbourhoods Golly writes otherwise
+    fn golly_rule(&self) -> Option<String> {
+        match (&self.mode, self.neighbourhood) {
+            (Mode::GameOfLife, Neighbourhood::Moore) => Some(self.gol_rule.to_string()),
+            (Mode::GameOfLife, Neighbourhood::VonNeumann) => Some(format!("{}V", self.gol_rule)),
+            _ => None,
+        }
+    }
+
+    /// the live cells (see [`Lenia::live_cells`]) as an RLE pattern cropped to their bounding
+    /// box, whose top-left corner is kept in Golly's `#CXRLE Pos=x,y` line (the grid's top-left
+    /// cell being at 0,0, y going down), with the rule in Game of Life; lines end with `\n`
+    pub fn to_rle(&self) -> String {
+        let cells = self.live_cells();
+        let rule = self
+            .golly_rule()
+            .map(|rule| format!(", rule = {rule}"))
+            .unwrap_or_default();
+        let Some((top, left, bottom, right)) = cells.iter().fold(None, |bounds, &(raw, col)| {
+            let (top, left, bottom, right) = bounds.unwrap_or((raw, col, raw, col));
+            Some((top.min(raw), left.min(col), bottom.max(raw), right.max(col)))
+        }) else {
+            return format!("x = 0, y = 0{rule}\n!\n");
+        };
+
+        // runs of a tag, trailing dead cells of a raw being left out and the raw ends of empty
+        // raws merged into one run
+        let mut runs: Vec<(usize, char)> = vec![];
+        let mut push = |count: usize, tag: char| match runs.last_mut() {
+            Some((last_count, last_tag)) if *last_tag == tag => *last_count += count,
+            _ if count > 0 => runs.push((count, tag)),
+            _ => {}
+        };
+        let (mut raw, mut col) = (top, left);
+        for &(cell_raw, cell_col) in &cells {
+            if cell_raw > raw {
+                push(cell_raw - raw, '$');
+                (raw, col) = (cell_raw, left);
+            }
+            push(cell_col - col, 'b');
+            push(1, 'o');
+            col = cell_col + 1;
+        }
+
+        let mut rle = format!(
+            "#CXRLE Pos={left},{top} Gen={}\nx = {}, y = {}{rule}\n",
+            self.generation,
+            right - left + 1,
+            bottom - top + 1,
+        );
+        let mut line = String::new();
+        let tokens = runs
+            .into_iter()
+            .map(|(count, tag)| match count {
+                1 => tag.to_string(),
+                count => format!("{count}{tag}"),
+            })
+            .chain(["!".to_string()]);
+        for token in tokens {
+            if line.len() + token.len() > RLE_LINE_LENGTH {
+                rle.push_str(&line);
+                rle.push('\n');
+                line.clear();
+            }
+            line.push_str(&token);
+        }
+        rle.push_str(&line);
+        rle.push('\n');
+        rle
+    }
+
+    /// write [`Lenia::to_rle`] to `path`, readable by Golly
+    pub fn save_rle(&self, path: &Path) -> std::io::Result<()> {
+        std::fs::write(path, self.to_rle())
+    }
+
+    /// bring the live cells of `pattern` to life, its top-left corner at (raw, col); cells out of
+    /// the grid are clipped
+    pub fn stamp_pattern(&mut self, pattern: &RlePattern, (raw, col): (usize, usize)) {
+        let (w, h) = (self.cells[0].len(), self.cells.len());
+        for &(pattern_raw, pattern_col) in &pattern.cells {
+            let (cell_raw, cell_col) = (raw + pattern_raw, col + pattern_col);
+            if cell_raw < h && cell_col < w {
+                self.cells[cell_raw][cell_col] = 1.0;
+                self.activate_around(cell_raw, cell_col);
+            }
+        }
+    }
+
+    /// the live cells (see [`Lenia::live_cells`]) in Golly's macrocell format: 8x8 leaves of `.`
+    /// and `*`, then `<level> <nw> <ne> <sw> <se>` nodes of 2^level cells referring to the earlier
+    /// lines (from 1, 0 being empty), the root last; Golly centers the root on 0,0, so it's large
+    /// enough for the grid's top-left cell to be at 0,0 there as with [`Lenia::to_rle`]
+    #[cfg(feature = "macrocell")]
+    pub fn to_macrocell(&self) -> String {
+        let (w, h) = (self.cells[0].len(), self.cells.len());
+        let mut level = 4;
+        while 1 << (level - 1) < w.max(h) {
+            level += 1;
+        }
+        let half = 1 << (level - 1);
+        let mut macrocell = MacrocellWriter::new();
+        let cells: Vec<_> = self
+            .live_cells()
+            .into_iter()
+            .map(|(raw, col)| (raw + half, col + half))
+            .collect();
+        macrocell.node(level, (0, 0), cells);
+
+        let mut text = "[M2] (lenia)\n".to_string();
+        if let Some(rule) = self.golly_rule() {
+            text.push_str(&format!("#R {rule}\n"));
+        }
+        text.push_str(&format!("#G {}\n", self.generation));
+        for line in macrocell.lines {
+            text.push_str(&line);
+            text.push('\n');
+        }
+        text
+    }
+
+    /// write [`Lenia::to_macrocell`] to `path`, readable by Golly
+    #[cfg(feature = "macrocell")]
+    pub fn save_macrocell(&self, path: &Path) -> std::io::Result<()> {
+        std::fs::write(path, self.to_macrocell())
+    }
+}
+
+/// the node lines of a macrocell file, each distinct node written once
+#[cfg(feature = "macrocell")]
+struct MacrocellWriter {
+    lines: Vec<String>,
+    /// line number (from 1) of every node already written
+    numbers: ahash::AHashMap<String, usize>,
+}
+
+#[cfg(feature = "macrocell")]
+impl MacrocellWriter {
+    fn new() -> Self {
+        Self {
+            lines: vec![],
+            numbers: ahash::AHashMap::new(),
+        }
+    }
+
+    /// line number of the node of 2^`level` cells at (raw, col) holding the live `cells`, 0
+    /// when there are none
+    fn node(
+        &mut self,
+        level: u32,
+        (raw, col): (usize, usize),
+        cells: Vec<(usize, usize)>,
+    ) -> usize {
+        if cells.is_empty() {
+            return 0;
+        }
+        let line = if level == 3 {
+            let mut rows = [[b'.'; 8]; 8];
+            for &(cell_raw, cell_col) in &cells {
+                rows[cell_raw - raw][cell_col - col] = b'*';
+            }
+            // the trailing dead cells of a raw and the trailing empty raws are left out
+            let rows: Vec<_> = rows
+                .iter()
+                .map(|row| {
+                    String::from_utf8_lossy(row)
+                        .trim_end_matches('.')
+                        .to_string()
+                })
+                .collect();
+            let used = rows
+                .iter()
+                .rposition(|row| !row.is_empty())
+                .map_or(0, |last| last + 1);
+            rows[..used].iter().map(|row| format!("{row}$")).collect()
+        } else {
+            let half = 1 << (level - 1);
+            let mut quadrants: [Vec<(usize, usize)>; 4] = Default::default();
+            for (cell_raw, cell_col) in cells {
+                let index =
+                    2 * usize::from(cell_raw >= raw + half) + usize::from(cell_col >= col + half);
+                quadrants[index].push((cell_raw, cell_col));
+            }
+            let [nw, ne, sw, se] = quadrants;
+            let children = [
+                self.node(level - 1, (raw, col), nw),
+                self.node(level - 1, (raw, col + half), ne),
+                self.node(level - 1, (raw + half, col), sw),
+                self.node(level - 1, (raw + half, col + half), se),
+            ];
+            format!(
+                "{level} {} {} {} {}",
+                children[0], children[1], children[2], children[3]
+            )
+        };
+        if let Some(&number) = self.numbers.get(&line) {
+            return number;
+        }
+        self.lines.push(line.clone());
+        self.numbers.insert(line, self.lines.len());
+        self.lines.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GLIDER: &str = "x = 3, y = 3, rule = B3/S23\nbo$2bo$3o!\n";
+    const R_PENTOMINO: &str = "x = 3, y = 3\nb2o$2o$bo!\n";
+
+    /// a dead `size`x`size` Game of Life grid with `pattern` stamped at (raw, col)
+    fn life_with(size: usize, pattern: &str, at: (usize, usize)) -> Lenia {
+        let mut lenia = Lenia::with_rng(
+            (size, size),
+            Some(((0, 0), (0, 0))),
+            Some(Mode::GameOfLife),
+            None,
+            None,
+            fastrand::Rng::with_seed(42),
+        );
+        lenia.clear();
+        lenia.stamp_pattern(&parse_rle(pattern).unwrap(), at);
+        lenia
+    }
+
+    #[test]
+    fn parse_rle_reads_the_header_and_the_runs() {
+        let glider = parse_rle(GLIDER).unwrap();
+        assert_eq!(glider.size, (3, 3));
+        assert_eq!(glider.rule.as_deref(), Some("B3/S23"));
+        assert_eq!(glider.position, None);
+        assert_eq!(glider.cells, [(0, 1), (1, 2), (2, 0), (2, 1), (2, 2)]);
+        assert!(parse_rle("bo$2bo$3o!").is_err());
+        assert!(parse_rle("x = 3, y = 3\nbo$2bq!").is_err());
+    }
+
+    #[test]
+    fn to_rle_round_trips_the_live_cells() {
+        for pattern in [GLIDER, R_PENTOMINO] {
+            let mut lenia = life_with(32, pattern, (5, 9));
+            // and again once the pattern has moved on
+            for _ in 0..2 {
+                let pattern = parse_rle(&lenia.to_rle()).unwrap();
+                let (left, top) = pattern.position.unwrap();
+                let cells: Vec<_> = pattern
+                    .cells
+                    .iter()
+                    .map(|&(raw, col)| (raw + top as usize, col + left as usize))
+                    .collect();
+                assert_eq!(cells, lenia.live_cells());
+                lenia.run(7);
+            }
+        }
+    }
+
+    #[test]
+    fn to_rle_of_an_empty_grid_has_no_cells() {
+        let mut lenia = life_with(16, GLIDER, (0, 0));
+        lenia.clear();
+        assert_eq!(lenia.to_rle(), "x = 0, y = 0, rule = B3/S23\n!\n");
+        assert!(parse_rle(&lenia.to_rle()).unwrap().cells.is_empty());
+    }
+
+    #[cfg(feature = "macrocell")]
+    #[test]
+    fn to_macrocell_writes_the_header_and_the_quadtree() {
+        let lenia = life_with(16, GLIDER, (1, 1));
+        // a 32x32 root with the grid in its south-east quadrant, the glider in the north-west
+        // 8x8 leaf of that
+        assert_eq!(
+            lenia.to_macrocell(),
+            "[M2] (lenia)\n#R B3/S23\n#G 0\n$..*$...*$.***$\n4 1 0 0 0\n5 0 0 0 2\n"
+        );
+    }
+}
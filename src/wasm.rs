@@ -0,0 +1,69 @@
+//! JS bindings, so that a browser frontend can drive the simulation and do its own rendering
+
+use crate::{Lenia, Mode};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen(js_name = Lenia)]
+pub struct WasmLenia(Lenia);
+
+#[wasm_bindgen(js_class = Lenia)]
+impl WasmLenia {
+    /// random Lenia (or Game of Life when `game_of_life`) grid of `width`x`height` cells
+    #[wasm_bindgen(constructor)]
+    pub fn new(width: usize, height: usize, game_of_life: bool) -> Self {
+        Self(Lenia::new(
+            (width, height),
+            None,
+            Some(if game_of_life {
+                Mode::GameOfLife
+            } else {
+                Mode::Lenia
+            }),
+            None,
+            None,
+        ))
+    }
+
+    pub fn step(&mut self) {
+        self.0.compute_next_frame();
+    }
+
+    pub fn width(&self) -> usize {
+        self.0.cells[0].len()
+    }
+
+    pub fn height(&self) -> usize {
+        self.0.cells.len()
+    }
+
+    /// grid as a flat `Float32Array`, raw after raw
+    pub fn cells(&self) -> Vec<f32> {
+        let mut buffer = vec![0.0; self.width() * self.height()];
+        self.0.copy_cells_into(&mut buffer);
+        buffer
+    }
+
+    /// same as `cells` but reusing a `Float32Array` of width * height cells
+    #[wasm_bindgen(js_name = copyCellsInto)]
+    pub fn copy_cells_into(&self, buffer: &mut [f32]) {
+        self.0.copy_cells_into(buffer);
+    }
+}
+
+// the bindings build without the window, so the core has to run without it too
+#[cfg(all(test, not(feature = "gui")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bindings_step_a_grid_without_the_gui() {
+        let mut lenia = WasmLenia::new(24, 16, false);
+        let before = lenia.cells();
+        assert_eq!(before.len(), 24 * 16);
+        lenia.step();
+        let mut after = vec![0.0; 24 * 16];
+        lenia.copy_cells_into(&mut after);
+        assert_ne!(after, before);
+        assert!(after.iter().all(|cell| (0.0..=1.0).contains(cell)));
+    }
+}
@@ -0,0 +1,108 @@
+//! Live tuning, reapplying a parameters file to the running simulation whenever it changes
+
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::{
+    path::{Path, PathBuf},
+    sync::mpsc::{channel, Receiver},
+};
+
+pub struct ParamsWatcher {
+    path: PathBuf,
+    events: Receiver<notify::Result<notify::Event>>,
+    /// stops watching when dropped
+    _watcher: RecommendedWatcher,
+}
+
+impl ParamsWatcher {
+    /// watch the directory of `path` rather than the file itself, as editors often save by
+    /// replacing the file, which would end a watch on it
+    pub fn new(path: &Path) -> notify::Result<Self> {
+        let (sender, events) = channel();
+        let mut watcher = notify::recommended_watcher(sender)?;
+        let directory = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        watcher.watch(directory, RecursiveMode::NonRecursive)?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            events,
+            _watcher: watcher,
+        })
+    }
+
+    /// whether the file was written, created or moved in since the last call, without blocking
+    pub fn has_changed(&self) -> bool {
+        let mut has_changed = false;
+        for event in self.events.try_iter() {
+            match event {
+                Ok(event)
+                    if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+                        && event
+                            .paths
+                            .iter()
+                            .any(|path| path.file_name() == self.path.file_name()) =>
+                {
+                    has_changed = true
+                }
+                Ok(_) => (),
+                Err(err) => eprintln!("failed to watch {}: {err}", self.path.display()),
+            }
+        }
+        has_changed
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reload_params;
+    use lenia::{Lenia, Preset};
+    use std::time::{Duration, Instant};
+
+    /// whether `watcher` saw a change within a few seconds
+    fn wait_for_change(watcher: &ParamsWatcher) -> bool {
+        let since = Instant::now();
+        while since.elapsed() < Duration::from_secs(5) {
+            if watcher.has_changed() {
+                return true;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        false
+    }
+
+    #[test]
+    fn changed_params_files_are_applied_and_invalid_ones_rejected() {
+        let dir = std::env::temp_dir().join(format!("lenia-watch-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("params.toml");
+        let mut lenia = Lenia::builder().dimensions(32, 32).seed(1).build().unwrap();
+        let mut preset = Preset::from_lenia(String::new(), &lenia);
+        std::fs::write(&path, toml::to_string(&preset).unwrap()).unwrap();
+        let watcher = ParamsWatcher::new(&path).unwrap();
+        assert!(!watcher.has_changed());
+
+        preset.delta_t = 0.25;
+        preset.kernel_radius = (7, 5);
+        preset.kernels[0].mu = 0.2;
+        std::fs::write(&path, toml::to_string(&preset).unwrap()).unwrap();
+        let saw_change = wait_for_change(&watcher);
+        reload_params(watcher.path(), &mut lenia);
+        let applied = Preset::from_lenia(String::new(), &lenia);
+
+        std::fs::write(&path, "delta_t = \"fast\"").unwrap();
+        let saw_invalid_change = wait_for_change(&watcher);
+        reload_params(watcher.path(), &mut lenia);
+        let kept = Preset::from_lenia(String::new(), &lenia);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(saw_change && saw_invalid_change);
+        assert_eq!(applied, preset);
+        assert_eq!(kept, preset);
+    }
+}